@@ -62,7 +62,7 @@ where
 {
     let v_ = v.borrow();
     let p_ = p.borrow();
-    ((p_.x - v_.x).powi(2) * (p_.y - v_.y).powi(2)).sqrt()
+    ((p_.x - v_.x).powi(2) + (p_.y - v_.y).powi(2)).sqrt()
 }
 
 fn lerp_two_points<F, P>(p: P, v: P, w: F) -> Point2<F>
@@ -322,6 +322,29 @@ mod tests {
         assert_eq!(expected, result);
     }
 
+    #[test]
+    fn distance_to_segment_squared_adds_both_axes() {
+        let squared = |p: Point2<f64>| {
+            super::distance_to_segment_squared(p, Point2::new(0.0, 0.0), Point2::new(10.0, 0.0))
+        };
+        assert_eq!(squared(Point2::new(5.0, 3.0)), 9.0);
+        // past the end the distance is to the end point
+        assert_eq!(squared(Point2::new(13.0, 4.0)), 25.0);
+    }
+
+    #[test]
+    fn simplify_drops_points_close_to_a_diagonal() {
+        let points = [
+            Point2::new(0.0, 0.0),
+            Point2::new(5.0, 5.5),
+            Point2::new(10.0, 10.0),
+        ];
+        assert_eq!(
+            super::simplify(&points, 1.0),
+            vec![Point2::new(0.0, 0.0), Point2::new(10.0, 10.0)]
+        );
+    }
+
     #[test]
     fn flatness() {
         let expected = 9.0;
@@ -469,48 +492,28 @@ mod tests {
         let expected_arr = vec![
             [70.0, 240.0],
             [73.5552978515625, 231.71592712402344],
-            [77.1875, 223.7371826171875],
             [80.8929443359375, 216.0614776611328],
-            [84.66796875, 208.6865234375],
             [88.5089111328125, 201.6100311279297],
-            [92.412109375, 194.8297119140625],
             [96.3739013671875, 188.34327697753906],
             [100.390625, 182.1484375],
-            [104.4586181640625, 176.24290466308594],
             [108.57421875, 170.6243896484375],
-            [112.7337646484375, 165.2906036376953],
             [116.93359375, 160.2392578125],
-            [121.1700439453125, 155.4680633544922],
             [125.439453125, 150.9747314453125],
-            [129.7381591796875, 146.75697326660156],
             [134.0625, 142.8125],
-            [138.4088134765625, 139.13902282714844],
             [142.7734375, 135.7342529296875],
-            [147.1527099609375, 132.5959014892578],
             [151.54296875, 129.7216796875],
-            [155.9405517578125, 127.10929870605469],
             [160.341796875, 124.7564697265625],
-            [164.7430419921875, 122.66090393066406],
             [169.140625, 120.8203125],
-            [173.5308837890625, 119.23240661621094],
             [177.91015625, 117.8948974609375],
-            [182.2747802734375, 116.80549621582031],
             [186.62109375, 115.9619140625],
-            [190.9454345703125, 115.36186218261719],
             [195.244140625, 115.0030517578125],
-            [199.5135498046875, 114.88319396972656],
             [203.75, 115.0],
-            [207.9498291015625, 115.35118103027344],
             [212.109375, 115.9344482421875],
-            [216.2249755859375, 116.74751281738281],
             [220.29296875, 117.7880859375],
             [224.3096923828125, 119.05387878417969],
-            [228.271484375, 120.5426025390625],
             [232.1746826171875, 122.25196838378906],
             [236.015625, 124.1796875],
-            [239.7906494140625, 126.32347106933594],
             [243.49609375, 128.6810302734375],
-            [247.1282958984375, 131.2500762939453],
             [250.68359375, 134.0283203125],
             [257.548828125, 140.2032470703125],
             [264.0625, 147.1875],
@@ -0,0 +1,33 @@
+//! This example shows exporting rough shapes for a pen plotter as HPGL and
+//! G-code, with solid fills turned into hachure
+
+use palette::Srgba;
+use roughfeel::graphics::drawable::DrawOptionsBuilder;
+use roughfeel::graphics::drawable_maker::{Generator, RoughlyDrawableMakable};
+use roughfeel::graphics::drawable_ops::OpSet;
+use roughfeel::graphics::paint::FillStyle;
+use roughfeel::renderer_engine::plotter::{PlotterDocument, PlotterOptionsBuilder};
+
+/// cargo run --example plotter
+fn main() {
+    let options = DrawOptionsBuilder::default()
+        .stroke(Srgba::from_components((114u8, 87u8, 82u8, 255u8)).into_format())
+        .fill(Srgba::from_components((40u8, 80u8, 160u8, 255u8)).into_format())
+        .fill_style(FillStyle::Solid)
+        .build()
+        .unwrap();
+    let generator = Generator::<OpSet<f32>>::new(options.clone());
+    let rectangle = generator.rectangle(20.0, 20.0, 120.0, 80.0, &Some(options.clone()));
+    let circle = generator.circle(210.0, 110.0, 120.0, &Some(options));
+
+    let plotter_options = PlotterOptionsBuilder::default()
+        .page_height(200.0)
+        .build()
+        .unwrap();
+    let mut document = PlotterDocument::new(plotter_options);
+    document.add_drawables([&rectangle, &circle]);
+    document.save_hpgl("plotter.hpgl").expect("file save error");
+    document
+        .save_gcode("plotter.gcode")
+        .expect("file save error");
+}
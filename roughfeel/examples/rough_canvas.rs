@@ -0,0 +1,66 @@
+//! This example shows painting rough shapes in immediate mode through a
+//! RoughCanvas wrapping a common-piet render context
+
+use nalgebra::Point2;
+use palette::Srgba;
+use piet::{Color, RenderContext};
+use piet_common::kurbo::Rect;
+use piet_common::Device;
+use roughfeel::canvas::RoughCanvas;
+use roughfeel::graphics::drawable::DrawOptionsBuilder;
+use roughfeel::graphics::paint::FillStyle;
+use roughfeel::graphics::render_context::RoughlyCanvas;
+
+const WIDTH: usize = 500;
+const HEIGHT: usize = 500;
+
+/// cargo run --example rough_canvas
+fn main() {
+    let mut device = Device::new().unwrap();
+    let mut bitmap = device.bitmap_target(WIDTH, HEIGHT, 1.0).unwrap();
+    let mut rc = bitmap.render_context();
+    let background_color = Color::from_hex_str("96C0B7").unwrap();
+    rc.fill(
+        Rect::new(0.0, 0.0, WIDTH as f64, HEIGHT as f64),
+        &background_color,
+    );
+
+    let options = DrawOptionsBuilder::default()
+        .stroke(Srgba::from_components((114u8, 87u8, 82u8, 255u8)).into_format())
+        .fill(Srgba::from_components((254u8, 246u8, 201u8, 255u8)).into_format())
+        .fill_style(FillStyle::Hachure)
+        .build()
+        .unwrap();
+    let mut canvas = RoughCanvas::<_, f32>::with_options(&mut rc, options);
+    canvas.draw_rectangle(40.0, 40.0, 180.0, 120.0, &None);
+
+    canvas.save().expect("Failed to save canvas");
+    canvas.options_mut().fill_style = Some(FillStyle::CrossHatch);
+    canvas.draw_circle(360.0, 110.0, 140.0, &None);
+    canvas.restore().expect("Failed to restore canvas");
+
+    canvas.draw_polygon(
+        &[
+            Point2::new(60.0, 300.0),
+            Point2::new(240.0, 260.0),
+            Point2::new(200.0, 440.0),
+        ],
+        &None,
+    );
+    canvas.draw_curve(
+        &[
+            Point2::new(280.0, 300.0),
+            Point2::new(340.0, 260.0),
+            Point2::new(400.0, 420.0),
+            Point2::new(460.0, 320.0),
+        ],
+        &None,
+    );
+
+    rc.finish().unwrap();
+    std::mem::drop(rc);
+
+    bitmap
+        .save_to_file("rough_canvas.png")
+        .expect("file save error");
+}
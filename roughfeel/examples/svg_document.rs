@@ -0,0 +1,36 @@
+//! This example shows writing rough shapes straight into a standalone svg
+//! document, without going through a piet render context
+
+use palette::Srgba;
+use roughfeel::graphics::drawable::DrawOptionsBuilder;
+use roughfeel::graphics::drawable_maker::{Generator, RoughlyDrawableMakable};
+use roughfeel::graphics::drawable_ops::OpSet;
+use roughfeel::graphics::paint::{FillStyle, LineCap};
+use roughfeel::renderer_engine::svg_document::SvgDocument;
+
+const WIDTH: f64 = 300.0;
+const HEIGHT: f64 = 200.0;
+
+/// cargo run --example svg_document
+fn main() {
+    let options = DrawOptionsBuilder::default()
+        .stroke(Srgba::from_components((114u8, 87u8, 82u8, 255u8)).into_format())
+        .fill(Srgba::from_components((254u8, 246u8, 201u8, 255u8)).into_format())
+        .fill_style(FillStyle::Hachure)
+        .line_cap(LineCap::Round)
+        .build()
+        .unwrap();
+    let generator = Generator::<OpSet<f32>>::new(options.clone());
+    let rectangle = generator.rectangle(20.0, 20.0, 120.0, 80.0, &Some(options.clone()));
+    let circle = generator.circle(210.0, 110.0, 120.0, &Some(options));
+
+    let mut document = SvgDocument::new(WIDTH, HEIGHT);
+    document
+        .set_background(Some(
+            Srgba::from_components((150u8, 192u8, 183u8, 255u8)).into_format(),
+        ))
+        .add_drawables([&rectangle, &circle]);
+    document
+        .save_to_file("svg_document.svg")
+        .expect("file save error");
+}
@@ -1,64 +1,228 @@
-use derive_builder::Builder;
-use nalgebra::{Point2, Scalar};
+use std::fmt::Display;
+use std::ops::MulAssign;
+
+use nalgebra::Point2;
 use nalgebra_glm::RealNumber;
-use palette::Srgba;
-use rand_chacha::{rand_core::block::BlockRngCore, ChaCha8Core};
+use piet::RenderContext;
 
 use crate::graphics::drawable::DrawOptions;
+use crate::graphics::drawable_maker::{Generator, RoughlyDrawableMakable};
+use crate::graphics::drawable_ops::OpSet;
+use crate::graphics::render_context::RoughlyCanvas;
+use crate::renderer_engine::kurbo_drawable::KurboDrawable;
+use crate::renderer_engine::kurbo_drawable_maker::KurboDrawableMaker;
+
+/// An immediate-mode canvas that draws rough shapes straight into a piet
+/// [`RenderContext`].
+///
+/// Shapes drawn with `None` options use the canvas' current default options.
+/// [`RoughCanvas::save`] and [`RoughCanvas::restore`] save and restore those
+/// options together with the state of the wrapped render context.
+pub struct RoughCanvas<'a, R: RenderContext, F: RealNumber + MulAssign + Display> {
+    ctx: &'a mut R,
+    maker: KurboDrawableMaker<F, KurboDrawable<F>>,
+    options: DrawOptions,
+    saved_options: Vec<DrawOptions>,
+}
+
+impl<'a, R: RenderContext, F: RealNumber + MulAssign + Display> RoughCanvas<'a, R, F> {
+    pub fn new(ctx: &'a mut R) -> Self {
+        Self::with_options(
+            ctx,
+            Generator::<OpSet<F>>::default().default_options().clone(),
+        )
+    }
+
+    pub fn with_options(ctx: &'a mut R, options: DrawOptions) -> Self {
+        RoughCanvas {
+            ctx,
+            maker: KurboDrawableMaker::new(Generator::new(options.clone())),
+            options,
+            saved_options: vec![],
+        }
+    }
+
+    pub fn options(&self) -> &DrawOptions {
+        &self.options
+    }
+
+    pub fn options_mut(&mut self) -> &mut DrawOptions {
+        &mut self.options
+    }
+
+    pub fn set_options(&mut self, options: DrawOptions) -> &mut Self {
+        self.options = options;
+        self
+    }
+
+    /// Pushes the current default options and the render context state.
+    pub fn save(&mut self) -> Result<(), piet::Error> {
+        self.ctx.save()?;
+        self.saved_options.push(self.options.clone());
+        Ok(())
+    }
+
+    /// Pops the state pushed by the matching [`RoughCanvas::save`].
+    pub fn restore(&mut self) -> Result<(), piet::Error> {
+        if self.saved_options.is_empty() {
+            return Err(piet::Error::StackUnbalance);
+        }
+        self.ctx.restore()?;
+        self.options = self.saved_options.pop().expect("saved options");
+        Ok(())
+    }
+
+    /// The wrapped render context, for drawing anything that is not rough.
+    pub fn render_context(&mut self) -> &mut R {
+        self.ctx
+    }
 
-use crate::graphics::drawable_ops::{OpSet, OpSetType, OpType};
+    pub fn draw(&mut self, drawable: &KurboDrawable<F>) {
+        drawable.draw(self.ctx);
+    }
 
-pub struct Drawable2D<F: RealNumber> {
-    pub shape: String,
-    pub options: DrawOptions,
-    pub opsets: Vec<OpSet<F>>,
+    fn resolve(&self, options: &Option<DrawOptions>) -> Option<DrawOptions> {
+        Some(options.clone().unwrap_or_else(|| self.options.clone()))
+    }
 }
 
-pub struct RoughCanvasDrawer {}
+impl<'a, R: RenderContext, F: RealNumber + MulAssign + Display> RoughlyCanvas<F>
+    for RoughCanvas<'a, R, F>
+{
+    fn draw_line(&mut self, x1: F, y1: F, x2: F, y2: F, options: &Option<DrawOptions>) {
+        let drawable = self.maker.line(x1, y1, x2, y2, &self.resolve(options));
+        self.draw(&drawable);
+    }
 
-pub struct Canvas2D {}
+    fn draw_rectangle(&mut self, x: F, y: F, width: F, height: F, options: &Option<DrawOptions>) {
+        let drawable = self
+            .maker
+            .rectangle(x, y, width, height, &self.resolve(options));
+        self.draw(&drawable);
+    }
 
-pub struct Canvas2DContext {}
+    fn draw_ellipse(&mut self, x: F, y: F, width: F, height: F, options: &Option<DrawOptions>) {
+        let drawable = self
+            .maker
+            .ellipse(x, y, width, height, &self.resolve(options));
+        self.draw(&drawable);
+    }
 
-pub struct RoughCanvas {
-    drawer: RoughCanvasDrawer,
-    canvas: Canvas2D,
-    ctx: Canvas2DContext,
+    fn draw_circle(&mut self, x: F, y: F, diameter: F, options: &Option<DrawOptions>) {
+        let drawable = self.maker.circle(x, y, diameter, &self.resolve(options));
+        self.draw(&drawable);
+    }
+
+    fn draw_linear_path(
+        &mut self,
+        points: &[Point2<F>],
+        close: bool,
+        options: &Option<DrawOptions>,
+    ) {
+        let drawable = self
+            .maker
+            .linear_path(points, close, &self.resolve(options));
+        self.draw(&drawable);
+    }
+
+    fn draw_polygon(&mut self, points: &[Point2<F>], options: &Option<DrawOptions>) {
+        let drawable = self.maker.polygon(points, &self.resolve(options));
+        self.draw(&drawable);
+    }
+
+    fn draw_arc(
+        &mut self,
+        x: F,
+        y: F,
+        width: F,
+        height: F,
+        start: F,
+        stop: F,
+        closed: bool,
+        options: &Option<DrawOptions>,
+    ) {
+        let drawable = self.maker.arc(
+            x,
+            y,
+            width,
+            height,
+            start,
+            stop,
+            closed,
+            &self.resolve(options),
+        );
+        self.draw(&drawable);
+    }
+
+    fn draw_bezier_quadratic(
+        &mut self,
+        start: Point2<F>,
+        cp: Point2<F>,
+        end: Point2<F>,
+        options: &Option<DrawOptions>,
+    ) {
+        let drawable = self
+            .maker
+            .bezier_quadratic(start, cp, end, &self.resolve(options));
+        self.draw(&drawable);
+    }
+
+    fn draw_bezier_cubic(
+        &mut self,
+        start: Point2<F>,
+        cp1: Point2<F>,
+        cp2: Point2<F>,
+        end: Point2<F>,
+        options: &Option<DrawOptions>,
+    ) {
+        let drawable = self
+            .maker
+            .bezier_cubic(start, cp1, cp2, end, &self.resolve(options));
+        self.draw(&drawable);
+    }
+
+    fn draw_curve(&mut self, points: &[Point2<F>], options: &Option<DrawOptions>) {
+        let drawable = self.maker.curve(points, &self.resolve(options));
+        self.draw(&drawable);
+    }
+
+    fn draw_path(&mut self, svg_path: String, options: &Option<DrawOptions>) {
+        let drawable = self.maker.path(svg_path, &self.resolve(options));
+        self.draw(&drawable);
+    }
 }
 
-// impl RoughCanvas {
-//     fn draw<F: Float + Trig>(drawable: Drawable2D<F>) {
-//         for op in drawable.opsets {
-//             match op.op_set_type {
-//                 OpSetType::Path => {
-//                     ctx.save();
-//                     ctx.strokeStyle
-//                 },
-//                 OpSetType::FillPath => todo!(),
-//                 OpSetType::FillSketch => todo!(),
-//             }
-//         }
-//     }
-
-//     fn _drawToContext(ctx: CanvasContext2D, drawing: OpSet, fixedDecimals: number, rule: CanvasFillRule = 'nonzero') {
-//         ctx.beginPath();
-//         for item in drawing.ops {
-//           const data = ((typeof fixedDecimals === 'number') && fixedDecimals >= 0) ? (item.data.map((d) => +d.toFixed(fixedDecimals))) : item.data;
-//           switch (item.op) {
-//             case 'move':
-//               ctx.moveTo(data[0], data[1]);
-//               break;
-//             case 'bcurveTo':
-//               ctx.bezierCurveTo(data[0], data[1], data[2], data[3], data[4], data[5]);
-//               break;
-//             case 'lineTo':
-//               ctx.lineTo(data[0], data[1]);
-//               break;
-//           }
-//         }
-//         if (drawing.type === 'fillPath') {
-//           ctx.fill(rule);
-//         } else {
-//           ctx.stroke();
-//         }
-// }
+#[cfg(test)]
+mod tests {
+    use piet::NullRenderContext;
+
+    use super::RoughCanvas;
+    use crate::graphics::drawable::DrawOptionsBuilder;
+    use crate::graphics::render_context::RoughlyCanvas;
+
+    #[test]
+    fn save_and_restore_default_options() {
+        let mut ctx = NullRenderContext::new();
+        let mut canvas = RoughCanvas::<_, f32>::new(&mut ctx);
+        canvas.options_mut().roughness = Some(1.0);
+        canvas.save().unwrap();
+        canvas.options_mut().roughness = Some(3.0);
+        canvas.draw_rectangle(10.0, 10.0, 20.0, 20.0, &None);
+        canvas.restore().unwrap();
+        assert_eq!(canvas.options().roughness, Some(1.0));
+        assert!(canvas.restore().is_err());
+    }
+
+    #[test]
+    fn explicit_options_do_not_change_defaults() {
+        let mut ctx = NullRenderContext::new();
+        let mut canvas = RoughCanvas::<_, f64>::new(&mut ctx);
+        let options = DrawOptionsBuilder::default()
+            .stroke_width(4.0)
+            .build()
+            .unwrap();
+        canvas.draw_path("M 0 0 L 10 10".into(), &Some(options));
+        canvas.draw_circle(5.0, 5.0, 10.0, &None);
+        assert_eq!(canvas.options().stroke_width, Some(1.0));
+    }
+}
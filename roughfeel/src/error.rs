@@ -0,0 +1,90 @@
+use std::fmt::Display;
+
+use svgtypes::PathSegment;
+
+/// Errors reported by the fallible `try_*` drawing functions.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Error {
+    /// SVG path data that could not be parsed. `offset` is the byte offset in
+    /// the path string where parsing stopped.
+    InvalidPathData { offset: usize, message: String },
+    /// A path segment that cannot be drawn where it appears.
+    UnexpectedSegment(PathSegment),
+    /// Input that does not describe a drawable shape, such as non finite
+    /// coordinates or too few points.
+    DegenerateInput(String),
+    /// A number that could not be converted between `f64` and the drawing precision.
+    NumericConversion(String),
+    /// A font file that could not be read or parsed.
+    InvalidFont(String),
+    /// An image that could not be decoded or whose pixels do not match its size.
+    InvalidImage(String),
+}
+
+impl Error {
+    /// Converts a parser error into an [`Error::InvalidPathData`] for the path string `d`.
+    ///
+    /// `svgtypes` reports one based character positions, which are mapped back
+    /// to byte offsets into `d`.
+    pub(crate) fn from_path_data(d: &str, error: svgtypes::Error) -> Self {
+        let char_position = match error {
+            svgtypes::Error::UnexpectedData(pos)
+            | svgtypes::Error::InvalidChar(_, pos)
+            | svgtypes::Error::InvalidString(_, pos)
+            | svgtypes::Error::InvalidNumber(pos) => Some(pos),
+            svgtypes::Error::UnexpectedEndOfStream | svgtypes::Error::InvalidValue => None,
+        };
+        let offset = char_position
+            .and_then(|pos| d.char_indices().nth(pos.saturating_sub(1)))
+            .map(|(offset, _)| offset)
+            .unwrap_or(d.len());
+        Error::InvalidPathData {
+            offset,
+            message: error.to_string(),
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::InvalidPathData { offset, message } => {
+                write!(f, "invalid path data at byte {}: {}", offset, message)
+            }
+            Error::UnexpectedSegment(segment) => {
+                write!(f, "unexpected path segment {:?}", segment)
+            }
+            Error::DegenerateInput(reason) => write!(f, "degenerate input: {}", reason),
+            Error::NumericConversion(reason) => write!(f, "numeric conversion failed: {}", reason),
+            Error::InvalidFont(reason) => write!(f, "invalid font: {}", reason),
+            Error::InvalidImage(reason) => write!(f, "invalid image: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+#[cfg(test)]
+mod tests {
+    use super::Error;
+
+    #[test]
+    fn invalid_path_data_reports_byte_offset() {
+        let d = "M 1 1 L x";
+        let error = svgtypes::PathParser::from(d).find_map(|s| s.err()).unwrap();
+        match Error::from_path_data(d, error) {
+            Error::InvalidPathData { offset, .. } => assert_eq!(&d[offset..], "x"),
+            e => panic!("unexpected error {}", e),
+        }
+    }
+
+    #[test]
+    fn byte_offset_after_multibyte_characters() {
+        let d = "M 1 1 L 2 2 é";
+        let error = svgtypes::PathParser::from(d).find_map(|s| s.err()).unwrap();
+        match Error::from_path_data(d, error) {
+            Error::InvalidPathData { offset, .. } => assert_eq!(&d[offset..], "é"),
+            e => panic!("unexpected error {}", e),
+        }
+    }
+}
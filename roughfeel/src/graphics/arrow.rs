@@ -0,0 +1,206 @@
+use derive_builder::Builder;
+use nalgebra::{Point2, Vector2};
+use nalgebra_glm::RealNumber;
+
+use super::drawable::DrawOptions;
+use super::drawable_ops::OpSet;
+use super::renderer::{ellipse, line, polygon};
+use super::{_c, get_pi};
+
+/// The decoration drawn at one end of an arrow.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ArrowHead {
+    /// Two strokes meeting at the tip.
+    Open,
+    Triangle {
+        filled: bool,
+    },
+    Diamond {
+        filled: bool,
+    },
+    /// A circle touching the tip.
+    Circle {
+        filled: bool,
+    },
+    /// A stroke across the tip.
+    Bar,
+}
+
+#[derive(Clone, Debug, Builder)]
+#[builder(setter(strip_option))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ArrowOptions {
+    #[builder(default = "None")]
+    pub start: Option<ArrowHead>,
+    #[builder(default = "Some(ArrowHead::Open)")]
+    pub end: Option<ArrowHead>,
+    /// Length of the heads as a multiple of the stroke width.
+    #[builder(default = "10.0")]
+    pub head_size: f32,
+}
+
+impl Default for ArrowOptions {
+    fn default() -> Self {
+        ArrowOptionsBuilder::default()
+            .build()
+            .expect("failed to build default arrow options")
+    }
+}
+
+impl ArrowOptions {
+    pub(crate) fn head_length<F: RealNumber>(&self, o: &DrawOptions) -> F {
+        _c::<F>(self.head_size * o.stroke_width.unwrap_or(1.0))
+    }
+}
+
+impl ArrowHead {
+    pub fn is_filled(&self) -> bool {
+        match self {
+            ArrowHead::Triangle { filled }
+            | ArrowHead::Diamond { filled }
+            | ArrowHead::Circle { filled } => *filled,
+            _ => false,
+        }
+    }
+
+    /// How far back from the tip the shaft should stop so it does not run
+    /// through a closed head.
+    pub(crate) fn depth<F: RealNumber>(&self, length: F) -> F {
+        match self {
+            ArrowHead::Open | ArrowHead::Bar => F::zero(),
+            ArrowHead::Circle { .. } => length * _c(2.0 / 3.0),
+            _ => length,
+        }
+    }
+}
+
+/// Sketches `head` at `tip`, pointing along `direction`. Returns the region to
+/// fill for a filled head, if any, and the outline.
+pub(crate) fn arrow_head<F: RealNumber>(
+    head: ArrowHead,
+    tip: Point2<F>,
+    direction: Vector2<F>,
+    length: F,
+    o: &mut DrawOptions,
+) -> (Option<Vec<Point2<F>>>, OpSet<F>) {
+    let norm = direction.norm();
+    let back = if norm > F::zero() {
+        -direction / norm
+    } else {
+        Vector2::new(-F::one(), F::zero())
+    };
+    let side = Vector2::new(-back.y, back.x);
+    let half_width = length * _c(0.5);
+    let (outline, region) = match head {
+        ArrowHead::Open => {
+            let base = tip + back * length;
+            let mut outline = line(
+                tip.x,
+                tip.y,
+                base.x + side.x * half_width,
+                base.y + side.y * half_width,
+                o,
+            );
+            let other = line(
+                tip.x,
+                tip.y,
+                base.x - side.x * half_width,
+                base.y - side.y * half_width,
+                o,
+            );
+            outline.ops.extend(other.ops);
+            (outline, vec![])
+        }
+        ArrowHead::Bar => {
+            let (a, b) = (tip + side * half_width, tip - side * half_width);
+            (line(a.x, a.y, b.x, b.y, o), vec![])
+        }
+        ArrowHead::Triangle { .. } => {
+            let base = tip + back * length;
+            let points = vec![tip, base + side * half_width, base - side * half_width];
+            (polygon(&points, o), points)
+        }
+        ArrowHead::Diamond { .. } => {
+            let middle = tip + back * (length / _c(2.0));
+            let width = length * _c(0.35);
+            let points = vec![
+                tip,
+                middle + side * width,
+                tip + back * length,
+                middle - side * width,
+            ];
+            (polygon(&points, o), points)
+        }
+        ArrowHead::Circle { .. } => {
+            let radius = length / _c(3.0);
+            let center = tip + back * radius;
+            let steps = 24;
+            let points = (0..steps)
+                .map(|i| {
+                    let angle = get_pi::<F>() * _c(2.0) * _c::<F>(i as f32) / _c::<F>(steps as f32);
+                    center + Vector2::new(angle.cos(), angle.sin()) * radius
+                })
+                .collect();
+            let diameter = radius * _c(2.0);
+            (ellipse(center.x, center.y, diameter, diameter, o), points)
+        }
+    };
+    (head.is_filled().then_some(region), outline)
+}
+
+/// The part of the cubic `start`, `cp1`, `cp2`, `end` between parameters 0
+/// and `t`.
+pub(crate) fn split_cubic<F: RealNumber>(
+    start: Point2<F>,
+    cp1: Point2<F>,
+    cp2: Point2<F>,
+    end: Point2<F>,
+    t: F,
+) -> [Point2<F>; 4] {
+    let lerp = |a: Point2<F>, b: Point2<F>| a + (b - a) * t;
+    let (ab, bc, cd) = (lerp(start, cp1), lerp(cp1, cp2), lerp(cp2, end));
+    let (abc, bcd) = (lerp(ab, bc), lerp(bc, cd));
+    [start, ab, abc, lerp(abc, bcd)]
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::{Point2, Vector2};
+
+    use super::{arrow_head, split_cubic, ArrowHead};
+    use crate::graphics::drawable::DrawOptions;
+    use crate::graphics::drawable_ops::OpSetType;
+
+    #[test]
+    fn only_filled_heads_have_a_fill() {
+        let mut o = DrawOptions::default();
+        let tip = Point2::new(100.0, 0.0);
+        let (fill, outline) = arrow_head(
+            ArrowHead::Triangle { filled: true },
+            tip,
+            Vector2::new(1.0, 0.0),
+            10.0,
+            &mut o,
+        );
+        assert_eq!(fill.unwrap().len(), 3);
+        assert_eq!(outline.op_set_type, OpSetType::Path);
+        let (fill, _) = arrow_head(ArrowHead::Open, tip, Vector2::new(1.0, 0.0), 10.0, &mut o);
+        assert!(fill.is_none());
+    }
+
+    #[test]
+    fn split_cubic_keeps_the_start() {
+        let [a, b, c, d] = split_cubic(
+            Point2::new(0.0, 0.0),
+            Point2::new(0.0, 10.0),
+            Point2::new(10.0, 10.0),
+            Point2::new(10.0, 0.0),
+            0.5,
+        );
+        assert_eq!(a, Point2::new(0.0, 0.0));
+        assert_eq!(b, Point2::new(0.0, 5.0));
+        assert_eq!(c, Point2::new(2.5, 7.5));
+        assert_eq!(d, Point2::new(5.0, 7.5));
+    }
+}
@@ -0,0 +1,191 @@
+use std::fmt::Display;
+
+use nalgebra::{Point2, Vector2};
+use nalgebra_glm::RealNumber;
+
+use super::drawable::DrawOptions;
+use super::drawable_ops::{Op, OpSet, OpSetType, OpType};
+use super::{_c, _cc, _to_f64};
+
+/// Width changes of `BrushProfile::Random` are interpolated between this many
+/// random values along each stroke.
+const RANDOM_KNOTS: usize = 5;
+
+/// How the width of a brush stroke varies from its start to its end.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BrushProfile {
+    /// Swells from `start` to the full width in the middle and back to `end`.
+    /// Both ends are fractions of the stroke width, 0 gives a pointed end.
+    Taper { start: f32, end: f32 },
+    /// Pressure samples from 0 to 1, spread evenly along each stroke.
+    Pressure(Vec<f32>),
+    /// Wanders around the stroke width by up to `variation` of it.
+    Random { variation: f32 },
+}
+
+impl BrushProfile {
+    /// The fraction of the stroke width at `t`, the fraction of the stroke
+    /// length drawn so far. `knots` are the random values used by `Random`.
+    fn factor(&self, t: f64, knots: &[f64]) -> f64 {
+        let factor = match self {
+            BrushProfile::Taper { start, end } => {
+                let edge = f64::from(if t < 0.5 { *start } else { *end });
+                edge + (1.0 - edge) * (std::f64::consts::PI * t).sin()
+            }
+            BrushProfile::Pressure(samples) => {
+                let samples: Vec<f64> = samples.iter().map(|s| f64::from(*s)).collect();
+                interpolate(&samples, t).unwrap_or(1.0)
+            }
+            BrushProfile::Random { variation } => {
+                1.0 + f64::from(*variation) * interpolate(knots, t).unwrap_or(0.0)
+            }
+        };
+        factor.max(0.0)
+    }
+}
+
+fn interpolate(samples: &[f64], t: f64) -> Option<f64> {
+    match samples {
+        [] => None,
+        [only] => Some(*only),
+        _ => {
+            let position = t.clamp(0.0, 1.0) * (samples.len() - 1) as f64;
+            let i = (position.floor() as usize).min(samples.len() - 2);
+            let f = position - i as f64;
+            Some(samples[i] * (1.0 - f) + samples[i + 1] * f)
+        }
+    }
+}
+
+/// Turns each stroke of `set` into a closed outline around its centreline,
+/// with a width that follows `profile` and peaks at the stroke width. The
+/// result is a `BrushPath` set, which backends fill with the stroke colour.
+pub fn brush_stroke<F>(set: &OpSet<F>, profile: &BrushProfile, o: &mut DrawOptions) -> OpSet<F>
+where
+    F: RealNumber + Display,
+{
+    let width = _c::<F>(o.stroke_width.unwrap_or(1.0));
+    let mut ops = vec![];
+    for polyline in set.to_polylines(_c(0.25)) {
+        let knots: Vec<f64> = match profile {
+            BrushProfile::Random { .. } => {
+                (0..RANDOM_KNOTS).map(|_| o.random() * 2.0 - 1.0).collect()
+            }
+            _ => vec![],
+        };
+        let outline = stroke_outline(&polyline, width, |t| profile.factor(t, &knots));
+        for (i, p) in outline.iter().enumerate() {
+            ops.push(Op {
+                op: if i == 0 { OpType::Move } else { OpType::LineTo },
+                data: vec![p.x, p.y],
+            });
+        }
+    }
+    OpSet {
+        op_set_type: OpSetType::BrushPath,
+        ops,
+        size: set.size,
+        path: set.path.clone(),
+    }
+}
+
+/// The outline of `polyline` offset on both sides by half of `width` times
+/// `factor`, going out along the left side and back along the right.
+fn stroke_outline<F: RealNumber>(
+    polyline: &[Point2<F>],
+    width: F,
+    factor: impl Fn(f64) -> f64,
+) -> Vec<Point2<F>> {
+    let mut points = polyline.to_vec();
+    points.dedup();
+    if points.len() < 2 {
+        return vec![];
+    }
+    let mut lengths = vec![F::zero()];
+    for pair in points.windows(2) {
+        lengths.push(lengths[lengths.len() - 1] + (pair[1] - pair[0]).norm());
+    }
+    let total = _to_f64(lengths[lengths.len() - 1]);
+    let normal = |d: Vector2<F>| Vector2::new(-d.y, d.x) / d.norm();
+
+    let mut left = vec![];
+    let mut right = vec![];
+    for (i, p) in points.iter().enumerate() {
+        let before = (i > 0).then(|| normal(p - points[i - 1]));
+        let after = (i + 1 < points.len()).then(|| normal(points[i + 1] - p));
+        let side = before.or(after).unwrap();
+        let joined = before.unwrap_or(side) + after.unwrap_or(side);
+        // join segments with a miter, limited to twice the half width
+        let offset = if joined.norm() > _c(1.0e-6) {
+            let miter = joined / joined.norm();
+            miter / miter.dot(&side).max(_c(0.5))
+        } else {
+            side
+        };
+        let half = width * _cc::<F>(factor(_to_f64(lengths[i]) / total)) / _c(2.0);
+        left.push(p + offset * half);
+        right.push(p - offset * half);
+    }
+    left.extend(right.into_iter().rev());
+    left
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::relative_eq;
+    use nalgebra::Point2;
+
+    use super::{stroke_outline, BrushProfile};
+    use crate::graphics::drawable::DrawOptionsBuilder;
+    use crate::graphics::drawable_maker::{Generator, RoughlyDrawableMakable};
+    use crate::graphics::drawable_ops::{OpSet, OpSetType};
+
+    #[test]
+    fn taper_narrows_the_ends() {
+        let profile = BrushProfile::Taper {
+            start: 0.0,
+            end: 0.5,
+        };
+        let line = [
+            Point2::new(0.0, 0.0),
+            Point2::new(5.0, 0.0),
+            Point2::new(10.0, 0.0),
+        ];
+        let outline = stroke_outline(&line, 4.0, |t| profile.factor(t, &[]));
+        assert_eq!(outline.len(), 6);
+        assert!(relative_eq!(outline[0].y, 0.0));
+        assert!(relative_eq!(outline[1].y, 2.0));
+        assert!(relative_eq!(outline[2].y, 1.0));
+        assert!(relative_eq!(outline[4].y, -2.0));
+
+        let pressure = BrushProfile::Pressure(vec![0.0, 1.0]);
+        assert!(relative_eq!(pressure.factor(0.25, &[]), 0.25));
+        assert!(relative_eq!(
+            BrushProfile::Pressure(vec![]).factor(0.5, &[]),
+            1.0
+        ));
+    }
+
+    #[test]
+    fn brush_option_replaces_outlines_only() {
+        let generator = Generator::<OpSet<f64>>::default();
+        let options = DrawOptionsBuilder::default()
+            .stroke_width(3.0)
+            .brush(BrushProfile::Random { variation: 0.5 })
+            .build()
+            .unwrap();
+        let line = generator.line(0.0, 0.0, 100.0, 20.0, &Some(options.clone()));
+        assert!(!line.opsets.is_empty());
+        assert!(line
+            .opsets
+            .iter()
+            .all(|set| set.op_set_type == OpSetType::BrushPath));
+
+        let mut filled = options;
+        filled.fill = filled.stroke;
+        let square = generator.rectangle(0.0, 0.0, 50.0, 50.0, &Some(filled));
+        assert_eq!(square.opsets[0].op_set_type, OpSetType::FillSketch);
+        assert_eq!(square.opsets[1].op_set_type, OpSetType::BrushPath);
+    }
+}
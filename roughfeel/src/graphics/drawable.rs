@@ -1,12 +1,20 @@
+use std::fmt::Display;
+
 use derive_builder::Builder;
+use nalgebra::Point2;
 use nalgebra_glm::RealNumber;
 use palette::Srgba;
+use piet::kurbo::Affine;
+use points_on_curve::distance_to_segment_squared;
 use rand::{random, Rng, SeedableRng};
 use rand_chacha::ChaCha8Rng;
 
 use super::{
-    drawable_ops::OpSet,
-    paint::{FillStyle, LineCap, LineJoin},
+    _c,
+    brush::BrushProfile,
+    drawable_ops::{Bounds, OpSet, OpSetType},
+    geometry::transform_point,
+    paint::{FillRule, FillStyle, LineCap, LineJoin},
 };
 
 pub struct PathInfo {
@@ -14,10 +22,13 @@ pub struct PathInfo {
     pub stroke: Option<Srgba>,
     pub stroke_width: Option<f32>,
     pub fill: Option<Srgba>,
+    pub fill_rule: FillRule,
 }
 
 #[derive(Clone, Builder)]
 #[builder(setter(strip_option))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct DrawOptions {
     #[builder(default = "Some(2.0)")]
     pub max_randomness_offset: Option<f32>,
@@ -39,6 +50,18 @@ pub struct DrawOptions {
     pub fill: Option<Srgba>,
     #[builder(default = "None")]
     pub fill_style: Option<FillStyle>,
+    /// How overlapping and nested contours are filled, even-odd when unset.
+    #[builder(default = "None")]
+    pub fill_rule: Option<FillRule>,
+    /// Multiplies the alpha of the stroke colour, from 0 to 1.
+    #[builder(default = "None")]
+    pub stroke_opacity: Option<f32>,
+    /// Multiplies the alpha of the fill colour, from 0 to 1.
+    #[builder(default = "None")]
+    pub fill_opacity: Option<f32>,
+    /// Multiplies the alpha of everything the drawable paints, from 0 to 1.
+    #[builder(default = "None")]
+    pub opacity: Option<f32>,
     #[builder(default = "Some(-1.0)")]
     pub fill_weight: Option<f32>,
     #[builder(default = "Some(-41.0)")]
@@ -75,6 +98,12 @@ pub struct DrawOptions {
     pub preserve_vertices: Option<bool>,
     #[builder(default = "None")]
     pub fixed_decimal_place_digits: Option<f32>,
+    /// Draws outlines as filled brush strokes whose width follows this profile.
+    #[builder(default = "None")]
+    pub brush: Option<BrushProfile>,
+    /// Random number generator seeded from `seed` on first use. With the `serde`
+    /// feature its full state is serialized, so a deserialized copy continues
+    /// the same random sequence.
     #[builder(default = "None")]
     pub randomizer: Option<rand_chacha::ChaCha8Rng>,
 }
@@ -92,6 +121,10 @@ impl Default for DrawOptions {
             curve_step_count: Some(9.0),
             fill: None,
             fill_style: None,
+            fill_rule: None,
+            stroke_opacity: None,
+            fill_opacity: None,
+            opacity: None,
             fill_weight: Some(-1.0),
             hachure_angle: Some(-41.0),
             hachure_gap: Some(-1.0),
@@ -110,6 +143,7 @@ impl Default for DrawOptions {
             fill_line_dash: None,
             fill_line_dash_offset: None,
             fixed_decimal_place_digits: None,
+            brush: None,
             randomizer: None,
         }
     }
@@ -133,6 +167,23 @@ impl DrawOptions {
         }
     }
 
+    /// The stroke colour with `stroke_opacity` and `opacity` applied.
+    pub fn stroke_color(&self) -> Option<Srgba> {
+        self.stroke
+            .map(|color| self.faded(color, self.stroke_opacity))
+    }
+
+    /// The fill colour with `fill_opacity` and `opacity` applied.
+    pub fn fill_color(&self) -> Option<Srgba> {
+        self.fill.map(|color| self.faded(color, self.fill_opacity))
+    }
+
+    fn faded(&self, mut color: Srgba, opacity: Option<f32>) -> Srgba {
+        let factor = opacity.unwrap_or(1.0) * self.opacity.unwrap_or(1.0);
+        color.alpha *= factor.clamp(0.0, 1.0);
+        color
+    }
+
     pub fn set_hachure_angle(&mut self, angle: Option<f32>) -> &mut Self {
         self.hachure_angle = angle;
         self
@@ -156,6 +207,15 @@ where
     fn draw(shape: String, options: DrawOptions, sets: Vec<OpSetT>) -> Self;
 }
 
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(
+        serialize = "OpSetT: serde::Serialize, OpSetT::F: serde::Serialize",
+        deserialize = "OpSetT: serde::Deserialize<'de>, OpSetT::F: serde::Deserialize<'de>"
+    ))
+)]
 pub struct RoughlyDrawable<OpSetT: OpSetTrait>
 where
     OpSetT::F: RealNumber,
@@ -163,6 +223,10 @@ where
     pub shape: String,
     pub options: DrawOptions,
     pub opsets: Vec<OpSetT>,
+    /// Polygons bounding the filled area, before any roughness is applied.
+    /// Empty when the shape is not filled.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub fill_region: Vec<Vec<Point2<OpSetT::F>>>,
 }
 
 impl<AF: RealNumber> Drawable<OpSet<AF>> for RoughlyDrawable<OpSet<AF>> {
@@ -175,6 +239,133 @@ impl<AF: RealNumber> Drawable<OpSet<AF>> for RoughlyDrawable<OpSet<AF>> {
             shape: shape.into(),
             options: options.clone(),
             opsets: Vec::from_iter(sets.iter().cloned()),
+            fill_region: vec![],
+        }
+    }
+}
+
+impl<F: RealNumber> RoughlyDrawable<OpSet<F>> {
+    /// Maps the sketch and its fill region through `affine` without drawing
+    /// it again, so the randomness is kept. Stroke widths are not scaled.
+    pub fn transform(&mut self, affine: &Affine) {
+        self.opsets.iter_mut().for_each(|set| set.transform(affine));
+        for ring in self.fill_region.iter_mut() {
+            ring.iter_mut()
+                .for_each(|p| *p = transform_point(affine, p));
+        }
+    }
+}
+
+impl<F: RealNumber + Display> RoughlyDrawable<OpSet<F>> {
+    /// The box the drawable paints into, with the roughness of the sketch,
+    /// the extrema of its curves and half the width of its lines. `None` when
+    /// nothing is drawn.
+    pub fn bounds(&self) -> Option<Bounds<F>> {
+        let mut bounds = Bounds::of_points(self.fill_region.iter().flatten());
+        for set in self.opsets.iter() {
+            if let Some(set_bounds) = set.bounds() {
+                let set_bounds = set_bounds.inflate(self.line_width(set) / _c(2.0));
+                bounds = Some(bounds.map_or(set_bounds, |b| b.union(&set_bounds)));
+            }
         }
+        bounds
+    }
+
+    /// Whether `point` is within `tolerance` of a drawn line, or inside a
+    /// filled area or the fill region.
+    pub fn hit_test(&self, point: &Point2<F>, tolerance: F) -> bool {
+        let rule = self.options.fill_rule.unwrap_or_default();
+        if rule.contains(&self.fill_region, point) {
+            return true;
+        }
+        let flatness = (tolerance / _c(4.0)).max(_c(0.01));
+        self.opsets.iter().any(|set| {
+            let polylines = set.to_polylines(flatness);
+            match set.op_set_type {
+                OpSetType::FillPath => rule.contains(&polylines, point),
+                OpSetType::BrushPath => FillRule::NonZero.contains(&polylines, point),
+                OpSetType::Path | OpSetType::FillSketch => {
+                    let reach = tolerance + self.line_width(set) / _c(2.0);
+                    polylines.iter().any(|polyline| {
+                        polyline.windows(2).any(|segment| {
+                            distance_to_segment_squared(*point, segment[0], segment[1])
+                                <= reach * reach
+                        })
+                    })
+                }
+            }
+        })
+    }
+
+    /// The width backends stroke `set` with, zero for filled sets.
+    fn line_width(&self, set: &OpSet<F>) -> F {
+        let stroke_width = self.options.stroke_width.unwrap_or(1.0);
+        let width = match set.op_set_type {
+            OpSetType::Path => stroke_width,
+            OpSetType::FillSketch => {
+                let fill_weight = self.options.fill_weight.unwrap_or_default();
+                if fill_weight < 0.0 {
+                    stroke_width / 2.0
+                } else {
+                    fill_weight
+                }
+            }
+            OpSetType::FillPath | OpSetType::BrushPath => 0.0,
+        };
+        _c(width)
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use palette::Srgba;
+
+    use super::{DrawOptions, DrawOptionsBuilder, RoughlyDrawable};
+    use crate::graphics::drawable_maker::{Generator, RoughlyDrawableMakable};
+    use crate::graphics::drawable_ops::OpSet;
+    use crate::graphics::paint::{FillStyle, LineJoin};
+
+    fn styled_options() -> DrawOptions {
+        DrawOptionsBuilder::default()
+            .fill(Srgba::new(0.2, 0.4, 0.6, 0.8))
+            .fill_style(FillStyle::CrossHatch)
+            .line_join(LineJoin::Miter { limit: 4.0 })
+            .stroke_line_dash(vec![3.0, 1.0])
+            .seed(7_u64)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn drawable_json_round_trip() {
+        let generator = Generator::<OpSet<f64>>::default();
+        let drawable = generator.rectangle(0.0, 0.0, 40.0, 20.0, &Some(styled_options()));
+        let json = serde_json::to_string(&drawable).unwrap();
+        let restored: RoughlyDrawable<OpSet<f64>> = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.shape, drawable.shape);
+        assert_eq!(restored.opsets, drawable.opsets);
+        assert_eq!(restored.options.fill, drawable.options.fill);
+        assert_eq!(restored.options.fill_style, drawable.options.fill_style);
+        assert_eq!(restored.options.line_join, drawable.options.line_join);
+    }
+
+    #[test]
+    fn deserialized_options_render_identically() {
+        let generator = Generator::<OpSet<f32>>::default();
+        let mut options = styled_options();
+        // advance the generator so that its state, not just the seed, matters
+        options.random();
+        let json = serde_json::to_string(&options).unwrap();
+        let restored: DrawOptions = serde_json::from_str(&json).unwrap();
+        let original = generator.ellipse(10.0, 10.0, 30.0, 20.0, &Some(options));
+        let copy = generator.ellipse(10.0, 10.0, 30.0, 20.0, &Some(restored));
+        assert_eq!(original.opsets, copy.opsets);
+    }
+
+    #[test]
+    fn missing_fields_take_defaults() {
+        let options: DrawOptions = serde_json::from_str("{\"roughness\": 3.0}").unwrap();
+        assert_eq!(options.roughness, Some(3.0));
+        assert_eq!(options.seed, DrawOptions::default().seed);
     }
 }
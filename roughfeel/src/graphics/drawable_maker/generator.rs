@@ -1,25 +1,38 @@
-use std::fmt::{Display, Write};
+use std::collections::HashMap;
+use std::fmt::Display;
 use std::marker::PhantomData;
 
-use nalgebra::Point2;
+use nalgebra::{Point2, Vector2};
 use nalgebra_glm::RealNumber;
+use piet::kurbo::{Affine, PathEl};
 use points_on_curve::{curve_to_bezier, points_on_bezier_curves};
+use svgtypes::{PathParser, PathSegment};
 
+use crate::error::Error;
 use crate::graphics::_c;
+use crate::graphics::arrow::{arrow_head, split_cubic, ArrowHead, ArrowOptions};
+use crate::graphics::brush::brush_stroke;
 use crate::graphics::drawable::{
     DrawOptions, DrawOptionsBuilder, Drawable, OpSetTrait, PathInfo, RoughlyDrawable,
 };
 use crate::graphics::drawable_maker::RoughlyDrawableMakable;
-use crate::graphics::drawable_ops::{OpSet, OpSetType, OpType};
-use crate::graphics::geometry::{convert_bezier_quadratic_to_cubic, BezierQuadratic};
-use crate::graphics::paint::FillStyle;
-use crate::graphics::points_on_path::points_on_path;
+use crate::graphics::drawable_ops::{OpSet, OpSetType};
+use crate::graphics::filler::PatternFiller;
+use crate::graphics::geometry::{
+    convert_bezier_quadratic_to_cubic, transform_point, BezierQuadratic,
+};
+use crate::graphics::paint::{FillRule, FillStyle};
+use crate::graphics::path_builder::RoughPathBuilder;
+use crate::graphics::path_data::{ops_to_path_data, parse_path_segments, path_element_to_segment};
+use crate::graphics::points_on_path::points_on_segments;
 use crate::graphics::renderer::{
-    bezier_cubic, bezier_quadratic, curve, ellipse_with_params, generate_ellipse_params, line,
-    linear_path, pattern_fill_arc, pattern_fill_polygons, rectangle, solid_fill_polygon, svg_path,
+    arc_fill_points, bezier_cubic, bezier_quadratic, curve, ellipse_with_params,
+    generate_ellipse_params, line, linear_path, pattern_fill_polygons, rectangle,
+    regular_polygon_points, rounded_rectangle, rounded_rectangle_points, solid_fill_polygon,
+    star_points, superellipse_with_params, svg_path_segments,
 };
 
-use super::Generator;
+use super::{check_points, Generator, TransformSpace};
 
 impl<F: RealNumber, OpSetT: OpSetTrait<F = F>> Default for Generator<OpSetT> {
     fn default() -> Self {
@@ -28,6 +41,10 @@ impl<F: RealNumber, OpSetT: OpSetTrait<F = F>> Default for Generator<OpSetT> {
                 .seed(345_u64)
                 .build()
                 .expect("failed to build default options"),
+            fillers: HashMap::new(),
+            transform: Affine::IDENTITY,
+            hachure_space: TransformSpace::Local,
+            stroke_space: TransformSpace::Local,
             phantom_data_opsett: PhantomData,
         }
     }
@@ -37,67 +54,253 @@ impl<F: RealNumber> Generator<OpSet<F>> {
     pub fn new(options: DrawOptions) -> Self {
         Generator {
             default_options: options,
+            fillers: HashMap::new(),
+            transform: Affine::IDENTITY,
+            hachure_space: TransformSpace::Local,
+            stroke_space: TransformSpace::Local,
             phantom_data_opsett: PhantomData,
         }
     }
 
+    pub fn default_options(&self) -> &DrawOptions {
+        &self.default_options
+    }
+
+    /// Registers `filler` for shapes drawn with `FillStyle::Custom(name)`,
+    /// replacing any filler registered under the same name. Shapes whose
+    /// custom name has no filler registered are filled with hachure.
+    pub fn register_filler(
+        &mut self,
+        name: impl Into<String>,
+        filler: impl PatternFiller<F, Vec<Vec<Point2<F>>>> + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.fillers.insert(name.into(), Box::new(filler));
+        self
+    }
+
+    pub fn transform(&self) -> &Affine {
+        &self.transform
+    }
+
+    /// Sets the transform applied to every shape drawn from now on. Shapes are
+    /// sketched in local coordinates and then mapped through it.
+    pub fn set_transform(&mut self, transform: Affine) -> &mut Self {
+        self.transform = transform;
+        self
+    }
+
+    /// Sets whether `hachure_angle` and `hachure_gap` are measured before or
+    /// after the transform.
+    pub fn set_hachure_space(&mut self, space: TransformSpace) -> &mut Self {
+        self.hachure_space = space;
+        self
+    }
+
+    /// Sets whether stroke widths are scaled by the transform or kept as
+    /// given on the output.
+    pub fn set_stroke_space(&mut self, space: TransformSpace) -> &mut Self {
+        self.stroke_space = space;
+        self
+    }
+
+    /// How much the transform scales lengths, on average over all directions.
+    fn transform_scale(&self) -> f64 {
+        self.transform.determinant().abs().sqrt()
+    }
+
+    /// Pattern fills `polygons`, looking up custom fill styles in the registry.
+    /// Device space hachure is laid out on the transformed polygons and mapped
+    /// back, so it ends up at the requested angle and gap after the transform.
+    fn pattern_fill(&self, polygons: Vec<Vec<Point2<F>>>, o: &mut DrawOptions) -> OpSet<F> {
+        let device = self.hachure_space == TransformSpace::Device
+            && self.transform != Affine::IDENTITY
+            && self.transform_scale() > 0.0;
+        let polygons = if device {
+            polygons
+                .iter()
+                .map(|ring| {
+                    ring.iter()
+                        .map(|p| transform_point(&self.transform, p))
+                        .collect()
+                })
+                .collect()
+        } else {
+            polygons
+        };
+        let mut set = match o.fill_style.as_ref() {
+            Some(FillStyle::Custom(name)) if self.fillers.contains_key(name) => {
+                self.fillers[name].fill_polygons(polygons, o)
+            }
+            _ => pattern_fill_polygons(polygons, o),
+        };
+        if device {
+            set.transform(&self.transform.inverse());
+        }
+        set
+    }
+
+    /// Wraps the op sets into a drawable, turning outlines into brush strokes
+    /// when the options ask for them.
     fn d(
         &self,
         name: String,
         op_sets: &[OpSet<F>],
+        fill_region: Vec<Vec<Point2<F>>>,
         options: &Option<DrawOptions>,
-    ) -> RoughlyDrawable<OpSet<F>> {
-        RoughlyDrawable::<OpSet<F>>::draw(
+    ) -> RoughlyDrawable<OpSet<F>>
+    where
+        F: RealNumber + Display,
+    {
+        let mut drawable = RoughlyDrawable::<OpSet<F>>::draw(
             name.into(),
             options
                 .clone()
                 .unwrap_or_else(|| self.default_options.clone()),
             Vec::from_iter(op_sets.iter().cloned()),
-        )
+        );
+        drawable.fill_region = fill_region;
+        let transformed = self.transform != Affine::IDENTITY;
+        let scale = self.transform_scale() as f32;
+        let device_widths =
+            transformed && self.stroke_space == TransformSpace::Device && scale > 0.0;
+        if let Some(profile) = drawable.options.brush.clone() {
+            // brush outlines are areas, so device widths are undone in advance
+            let width = drawable.options.stroke_width;
+            if device_widths {
+                drawable.options.stroke_width = width.map(|w| w / scale);
+            }
+            for set in drawable.opsets.iter_mut() {
+                if set.op_set_type == OpSetType::Path {
+                    *set = brush_stroke(set, &profile, &mut drawable.options);
+                }
+            }
+            drawable.options.stroke_width = width;
+        }
+        if transformed {
+            if self.stroke_space == TransformSpace::Local {
+                let o = &mut drawable.options;
+                o.stroke_width = o.stroke_width.map(|w| w * scale);
+                o.fill_weight = o.fill_weight.map(|w| if w < 0.0 { w } else { w * scale });
+            }
+            drawable.transform(&self.transform);
+        }
+        drawable
     }
 
-    pub fn ops_to_path(mut drawing: OpSet<F>, fixed_decimals: Option<u32>) -> String
+    /// Sketches a path given as segments, shared by the string and kurbo path
+    /// inputs. Nothing is drawn when the segments cannot be sketched.
+    fn path_segments(
+        &self,
+        segments: &[PathSegment],
+        options: &Option<DrawOptions>,
+    ) -> RoughlyDrawable<OpSet<F>>
     where
         F: RealNumber + Display,
     {
-        // Convert to path string
-        // https://developer.mozilla.org/en-US/docs/Web/SVG/Tutorial/Paths
-        let mut path = String::new();
-
-        for item in drawing.ops.iter_mut() {
-            if let Some(fd) = fixed_decimals {
-                let pow: u32 = 10u32.pow(fd);
-                item.data.iter_mut().for_each(|p| {
-                    *p = (*p * F::from_u32(pow).unwrap()).round() / F::from_u32(pow).unwrap();
-                });
+        self.try_path_segments(segments, options)
+            .unwrap_or_else(|_| self.d("path".to_owned(), &[], vec![], options))
+    }
+
+    /// Like [`Self::path_segments`], but reports segments that cannot be
+    /// flattened or converted to `F`.
+    fn try_path_segments(
+        &self,
+        segments: &[PathSegment],
+        options: &Option<DrawOptions>,
+    ) -> Result<RoughlyDrawable<OpSet<F>>, Error>
+    where
+        F: RealNumber + Display,
+    {
+        let mut options = options.clone().unwrap_or(self.default_options.clone());
+        let mut paths = vec![];
+        if segments.is_empty() {
+            return Ok(self.d("path".to_owned(), &paths, vec![], &Some(options)));
+        }
+        let simplified = options.simplification.map(|a| a < 1.0).unwrap_or(false);
+        let distance = if simplified {
+            _c::<F>(4.0) - _c::<F>(4.0) * _c::<F>(options.simplification.unwrap())
+        } else {
+            (_c::<F>(1.0) + _c::<F>(options.roughness.unwrap_or(1.0))) / _c::<F>(2.0)
+        };
+
+        let sets = points_on_segments(segments, Some(_c(1.0)), Some(distance))?;
+        for set in sets.iter() {
+            check_points("path", set, 0)?;
+        }
+        let mut fill_region = vec![];
+        if options.fill.is_some() {
+            if options.fill_style == Some(FillStyle::Solid) {
+                paths.push(solid_fill_polygon(&sets, &mut options));
+            } else {
+                paths.push(self.pattern_fill(sets.clone(), &mut options));
             }
+            fill_region = sets.clone();
+        }
 
-            match item.op {
-                OpType::Move => {
-                    write!(&mut path, "L{} {} ", item.data[0], item.data[1])
-                        .expect("Failed to write path string");
-                }
-                OpType::BCurveTo => {
-                    write!(
-                        &mut path,
-                        "C{} {}, {} {}, {} {} ",
-                        item.data[0],
-                        item.data[1],
-                        item.data[2],
-                        item.data[3],
-                        item.data[4],
-                        item.data[5]
-                    )
-                    .expect("Failed to write path string");
-                }
-                OpType::LineTo => {
-                    write!(&mut path, "L{} {}, ", item.data[0], item.data[1])
-                        .expect("Failed to write path string");
+        if options.stroke.is_some() {
+            if simplified {
+                sets.iter()
+                    .for_each(|s| paths.push(linear_path(s, false, &mut options)));
+            } else {
+                paths.push(svg_path_segments(segments, &mut options)?);
+            }
+        }
+
+        Ok(self.d("path".to_owned(), &paths, fill_region, &Some(options)))
+    }
+
+    /// Adds the heads of an arrow to its shaft. Filled heads use the stroke
+    /// colour with a solid fill when the options have no fill colour.
+    fn arrow_with_heads(
+        &self,
+        name: &str,
+        mut shaft: OpSet<F>,
+        heads: [(Option<ArrowHead>, Point2<F>, Vector2<F>); 2],
+        length: F,
+        mut options: DrawOptions,
+    ) -> RoughlyDrawable<OpSet<F>>
+    where
+        F: RealNumber + Display,
+    {
+        if options.fill.is_none()
+            && heads
+                .iter()
+                .any(|(h, _, _)| h.is_some_and(|h| h.is_filled()))
+        {
+            options.fill = options.stroke;
+            options.fill_style = Some(FillStyle::Solid);
+        }
+        let mut paths = vec![];
+        let mut fill_region = vec![];
+        for (head, tip, direction) in heads {
+            if let Some(head) = head {
+                let (region, outline) = arrow_head(head, tip, direction, length, &mut options);
+                // filled heads take the stroke colour, so without either
+                // there is nothing to paint them with
+                if let Some(region) = region.filter(|_| options.fill.is_some()) {
+                    if options.fill_style == Some(FillStyle::Solid) {
+                        paths.push(solid_fill_polygon(&vec![region.clone()], &mut options));
+                    } else {
+                        paths.push(self.pattern_fill(vec![region.clone()], &mut options));
+                    }
+                    fill_region.push(region);
                 }
+                shaft.ops.extend(outline.ops);
             }
         }
+        if options.stroke.is_some() {
+            paths.push(shaft);
+        }
+        self.d(name.to_owned(), &paths, fill_region, &Some(options))
+    }
 
-        path
+    pub fn ops_to_path(drawing: OpSet<F>, fixed_decimals: Option<u32>) -> String
+    where
+        F: RealNumber + Display,
+    {
+        // Convert to path string
+        // https://developer.mozilla.org/en-US/docs/Web/SVG/Tutorial/Paths
+        ops_to_path_data(&drawing.ops, fixed_decimals)
     }
 
     pub fn to_paths(drawable: RoughlyDrawable<OpSet<F>>) -> Vec<PathInfo>
@@ -106,20 +309,31 @@ impl<F: RealNumber> Generator<OpSet<F>> {
     {
         let sets = drawable.opsets;
         let o = drawable.options;
+        let fill_rule = o.fill_rule.unwrap_or_default();
         let mut path_infos = vec![];
         for drawing in sets.iter() {
             let path_info = match drawing.op_set_type {
                 OpSetType::Path => PathInfo {
                     d: Self::ops_to_path(drawing.clone(), None),
-                    stroke: o.stroke,
+                    stroke: o.stroke_color(),
                     stroke_width: o.stroke_width,
                     fill: None,
+                    fill_rule,
                 },
                 OpSetType::FillPath => PathInfo {
                     d: Self::ops_to_path(drawing.clone(), None),
                     stroke: None,
                     stroke_width: Some(0.0f32),
-                    fill: o.fill,
+                    fill: o.fill_color(),
+                    fill_rule,
+                },
+                OpSetType::BrushPath => PathInfo {
+                    d: Self::ops_to_path(drawing.clone(), None),
+                    stroke: None,
+                    stroke_width: Some(0.0f32),
+                    fill: o.stroke_color(),
+                    // brush outlines overlap themselves at sharp turns
+                    fill_rule: FillRule::NonZero,
                 },
                 OpSetType::FillSketch => {
                     let fill_weight = if o.fill_weight.unwrap_or(0.0) < 0.0 {
@@ -129,9 +343,10 @@ impl<F: RealNumber> Generator<OpSet<F>> {
                     };
                     PathInfo {
                         d: Self::ops_to_path(drawing.clone(), None),
-                        stroke: o.fill,
+                        stroke: o.fill_color(),
                         stroke_width: Some(fill_weight),
                         fill: None,
+                        fill_rule,
                     }
                 }
             };
@@ -166,6 +381,7 @@ impl<F: RealNumber + Display> RoughlyDrawableMakable<F, OpSet<F>, RoughlyDrawabl
                     .clone()
                     .unwrap_or_else(|| self.default_options.clone()),
             )],
+            vec![],
             options,
         );
         x
@@ -187,6 +403,7 @@ impl<F: RealNumber + Display> RoughlyDrawableMakable<F, OpSet<F>, RoughlyDrawabl
             .clone()
             .unwrap_or_else(|| self.default_options.clone());
         let outline = rectangle(x, y, width, height, &mut options);
+        let mut fill_region = vec![];
         if options.fill.is_some() {
             let points = vec![
                 Point2::new(x, y),
@@ -195,16 +412,57 @@ impl<F: RealNumber + Display> RoughlyDrawableMakable<F, OpSet<F>, RoughlyDrawabl
                 Point2::new(x, y + height),
             ];
             if options.fill_style == Some(FillStyle::Solid) {
-                paths.push(solid_fill_polygon(&vec![points], &mut options));
+                paths.push(solid_fill_polygon(&vec![points.clone()], &mut options));
             } else {
-                paths.push(pattern_fill_polygons(vec![points], &mut options));
+                paths.push(self.pattern_fill(vec![points.clone()], &mut options));
             }
+            fill_region = vec![points];
         }
         if options.stroke.is_some() {
             paths.push(outline);
         }
 
-        self.d("rectangle".to_owned(), &paths, &Some(options))
+        self.d("rectangle".to_owned(), &paths, fill_region, &Some(options))
+    }
+
+    fn rounded_rectangle(
+        &self,
+        x: F,
+        y: F,
+        width: F,
+        height: F,
+        radii: [F; 4],
+        options: &Option<DrawOptions>,
+    ) -> RoughlyDrawable<OpSet<F>>
+    where
+        F: RealNumber,
+    {
+        let mut paths = vec![];
+        let mut options = options
+            .clone()
+            .unwrap_or_else(|| self.default_options.clone());
+        let outline = rounded_rectangle(x, y, width, height, radii, &mut options);
+        let mut fill_region = vec![];
+        if options.fill.is_some() {
+            let steps = options.curve_step_count.unwrap_or(9.0).max(1.0) as usize;
+            let points = rounded_rectangle_points(x, y, width, height, radii, steps);
+            if options.fill_style == Some(FillStyle::Solid) {
+                paths.push(solid_fill_polygon(&vec![points.clone()], &mut options));
+            } else {
+                paths.push(self.pattern_fill(vec![points.clone()], &mut options));
+            }
+            fill_region = vec![points];
+        }
+        if options.stroke.is_some() {
+            paths.push(outline);
+        }
+
+        self.d(
+            "rounded_rectangle".to_owned(),
+            &paths,
+            fill_region,
+            &Some(options),
+        )
     }
 
     fn ellipse(
@@ -224,22 +482,24 @@ impl<F: RealNumber + Display> RoughlyDrawableMakable<F, OpSet<F>, RoughlyDrawabl
             .unwrap_or_else(|| self.default_options.clone());
         let ellipse_params = generate_ellipse_params(width, height, &mut options);
         let ellipse_response = ellipse_with_params(x, y, &mut options, &ellipse_params);
+        let mut fill_region = vec![];
         if options.fill.is_some() {
             if options.fill_style == Some(FillStyle::Solid) {
                 let mut shape = ellipse_with_params(x, y, &mut options, &ellipse_params).opset;
                 shape.op_set_type = OpSetType::FillPath;
                 paths.push(shape);
             } else {
-                paths.push(pattern_fill_polygons(
-                    vec![ellipse_response.estimated_points],
+                paths.push(self.pattern_fill(
+                    vec![ellipse_response.estimated_points.clone()],
                     &mut options,
                 ));
             }
+            fill_region = vec![ellipse_response.estimated_points];
         }
         if options.stroke.is_some() {
             paths.push(ellipse_response.opset);
         }
-        self.d("ellipse".to_owned(), &paths, &Some(options))
+        self.d("ellipse".to_owned(), &paths, fill_region, &Some(options))
     }
 
     fn circle(
@@ -272,6 +532,7 @@ impl<F: RealNumber + Display> RoughlyDrawableMakable<F, OpSet<F>, RoughlyDrawabl
         self.d(
             "linear_path".to_owned(),
             &[linear_path(points, close, &mut options)],
+            vec![],
             &Some(options),
         )
     }
@@ -305,6 +566,7 @@ impl<F: RealNumber + Display> RoughlyDrawableMakable<F, OpSet<F>, RoughlyDrawabl
             true,
             &mut options,
         );
+        let mut fill_region = vec![];
         if closed && options.fill.is_some() {
             if options.fill_style == Some(FillStyle::Solid) {
                 options.disable_multi_stroke = Some(true);
@@ -322,21 +584,15 @@ impl<F: RealNumber + Display> RoughlyDrawableMakable<F, OpSet<F>, RoughlyDrawabl
                 shape.op_set_type = OpSetType::FillPath;
                 paths.push(shape);
             } else {
-                paths.push(pattern_fill_arc(
-                    x,
-                    y,
-                    width,
-                    height,
-                    start,
-                    stop,
-                    &mut options,
-                ));
+                let points = arc_fill_points(x, y, width, height, start, stop, &mut options);
+                paths.push(self.pattern_fill(vec![points], &mut options));
             }
+            fill_region = vec![arc_region(x, y, width, height, start, stop)];
         }
         if options.stroke.is_some() {
             paths.push(outline);
         }
-        self.d("arc".to_owned(), &paths, &Some(options))
+        self.d("arc".to_owned(), &paths, fill_region, &Some(options))
     }
 
     fn bezier_quadratic(
@@ -355,6 +611,7 @@ impl<F: RealNumber + Display> RoughlyDrawableMakable<F, OpSet<F>, RoughlyDrawabl
             .unwrap_or_else(|| self.default_options.clone());
 
         let outline = bezier_quadratic(start, cp, end, &mut options);
+        let mut fill_region = vec![];
 
         if options.fill.is_some() {
             // The fill algorithms expect at least 4 points of a cubic curve, else they panic
@@ -367,17 +624,18 @@ impl<F: RealNumber + Display> RoughlyDrawableMakable<F, OpSet<F>, RoughlyDrawabl
                 Some(_c::<F>(1.0) + _c::<F>(options.roughness.unwrap_or(0.0)) / _c(2.0)),
             );
             if options.fill_style == Some(FillStyle::Solid) {
-                paths.push(solid_fill_polygon(&vec![poly_points], &mut options));
+                paths.push(solid_fill_polygon(&vec![poly_points.clone()], &mut options));
             } else {
-                paths.push(pattern_fill_polygons(&mut vec![poly_points], &mut options));
+                paths.push(self.pattern_fill(vec![poly_points.clone()], &mut options));
             }
+            fill_region = vec![poly_points];
         }
 
         if options.stroke.is_some() {
             paths.push(outline);
         }
 
-        self.d("curve".to_owned(), &paths, &Some(options))
+        self.d("curve".to_owned(), &paths, fill_region, &Some(options))
     }
 
     fn bezier_cubic(
@@ -397,6 +655,7 @@ impl<F: RealNumber + Display> RoughlyDrawableMakable<F, OpSet<F>, RoughlyDrawabl
             .unwrap_or_else(|| self.default_options.clone());
 
         let outline = bezier_cubic(start, cp1, cp2, end, &mut options);
+        let mut fill_region = vec![];
 
         if options.fill.is_some() {
             let crv = vec![start, cp1, cp2, end];
@@ -407,17 +666,18 @@ impl<F: RealNumber + Display> RoughlyDrawableMakable<F, OpSet<F>, RoughlyDrawabl
                 Some(_c::<F>(1.0) + _c::<F>(options.roughness.unwrap_or(0.0)) / _c(2.0)),
             );
             if options.fill_style == Some(FillStyle::Solid) {
-                paths.push(solid_fill_polygon(&vec![poly_points], &mut options));
+                paths.push(solid_fill_polygon(&vec![poly_points.clone()], &mut options));
             } else {
-                paths.push(pattern_fill_polygons(&mut vec![poly_points], &mut options));
+                paths.push(self.pattern_fill(vec![poly_points.clone()], &mut options));
             }
+            fill_region = vec![poly_points];
         }
 
         if options.stroke.is_some() {
             paths.push(outline);
         }
 
-        self.d("curve".to_owned(), &paths, &Some(options))
+        self.d("curve".to_owned(), &paths, fill_region, &Some(options))
     }
 
     fn curve(
@@ -433,6 +693,7 @@ impl<F: RealNumber + Display> RoughlyDrawableMakable<F, OpSet<F>, RoughlyDrawabl
             .clone()
             .unwrap_or_else(|| self.default_options.clone());
         let outline = curve(points, &mut options);
+        let mut fill_region = vec![];
         if options.fill.is_some() && points.len() >= 3 {
             let curve = curve_to_bezier(points, _c(0.0));
             if let Some(crv) = curve {
@@ -442,10 +703,11 @@ impl<F: RealNumber + Display> RoughlyDrawableMakable<F, OpSet<F>, RoughlyDrawabl
                     Some(_c::<F>(1.0) + _c::<F>(options.roughness.unwrap_or(0.0)) / _c(2.0)),
                 );
                 if options.fill_style == Some(FillStyle::Solid) {
-                    paths.push(solid_fill_polygon(&vec![poly_points], &mut options));
+                    paths.push(solid_fill_polygon(&vec![poly_points.clone()], &mut options));
                 } else {
-                    paths.push(pattern_fill_polygons(&mut vec![poly_points], &mut options));
+                    paths.push(self.pattern_fill(vec![poly_points.clone()], &mut options));
                 }
+                fill_region = vec![poly_points];
             }
         }
 
@@ -453,7 +715,100 @@ impl<F: RealNumber + Display> RoughlyDrawableMakable<F, OpSet<F>, RoughlyDrawabl
             paths.push(outline);
         }
 
-        self.d("curve".to_owned(), &paths, &Some(options))
+        self.d("curve".to_owned(), &paths, fill_region, &Some(options))
+    }
+
+    fn arrow(
+        &self,
+        start: Point2<F>,
+        end: Point2<F>,
+        arrow: &ArrowOptions,
+        options: &Option<DrawOptions>,
+    ) -> RoughlyDrawable<OpSet<F>> {
+        let mut options = options
+            .clone()
+            .unwrap_or_else(|| self.default_options.clone());
+        let length = arrow.head_length::<F>(&options);
+        let direction = end - start;
+        let total = direction.norm();
+        let unit = if total > F::zero() {
+            direction / total
+        } else {
+            Vector2::zeros()
+        };
+        // keep at least half of the shaft when the heads are large
+        let depth = |head: Option<ArrowHead>| {
+            head.map_or(F::zero(), |h| h.depth(length))
+                .min(total / _c(4.0))
+        };
+        let shaft_start = start + unit * depth(arrow.start);
+        let shaft_end = end - unit * depth(arrow.end);
+        let shaft = line(
+            shaft_start.x,
+            shaft_start.y,
+            shaft_end.x,
+            shaft_end.y,
+            &mut options,
+        );
+        self.arrow_with_heads(
+            "arrow",
+            shaft,
+            [
+                (arrow.start, start, -direction),
+                (arrow.end, end, direction),
+            ],
+            length,
+            options,
+        )
+    }
+
+    fn curved_arrow(
+        &self,
+        start: Point2<F>,
+        cp1: Point2<F>,
+        cp2: Point2<F>,
+        end: Point2<F>,
+        arrow: &ArrowOptions,
+        options: &Option<DrawOptions>,
+    ) -> RoughlyDrawable<OpSet<F>> {
+        let mut options = options
+            .clone()
+            .unwrap_or_else(|| self.default_options.clone());
+        let length = arrow.head_length::<F>(&options);
+        let tangent = |a: Point2<F>, points: [Point2<F>; 3]| {
+            points
+                .iter()
+                .map(|p| a - p)
+                .find(|v| v.norm() > F::zero())
+                .unwrap_or_else(Vector2::zeros)
+        };
+        let start_direction = tangent(start, [cp1, cp2, end]);
+        let end_direction = tangent(end, [cp2, cp1, start]);
+
+        // trim the curve where the heads begin, estimating the parameter
+        // from the length of the control polygon
+        let control_length = (cp1 - start).norm() + (cp2 - cp1).norm() + (end - cp2).norm();
+        let trim = |head: Option<ArrowHead>| {
+            let depth = head.map_or(F::zero(), |h| h.depth(length));
+            if control_length > F::zero() {
+                (F::one() - depth / control_length).max(_c(0.75))
+            } else {
+                F::one()
+            }
+        };
+        let [s, a, b, e] = split_cubic(start, cp1, cp2, end, trim(arrow.end));
+        let [e, b, a, s] = split_cubic(e, b, a, s, trim(arrow.start));
+        let shaft = bezier_cubic(s, a, b, e, &mut options);
+        self.arrow_with_heads(
+            "curved_arrow",
+            shaft,
+            [
+                (arrow.start, start, start_direction),
+                (arrow.end, end, end_direction),
+            ],
+            length,
+            options,
+        )
     }
 
     fn polygon(
@@ -469,57 +824,162 @@ impl<F: RealNumber + Display> RoughlyDrawableMakable<F, OpSet<F>, RoughlyDrawabl
             .unwrap_or_else(|| self.default_options.clone());
         let mut paths = vec![];
         let outline = linear_path(points, true, &mut options);
+        let mut fill_region = vec![];
         if options.fill.is_some() {
             if options.fill_style == Some(FillStyle::Solid) {
                 paths.push(solid_fill_polygon(&vec![points.to_vec()], &mut options));
             } else {
-                paths.push(pattern_fill_polygons(
-                    &mut vec![points.to_vec()],
-                    &mut options,
-                ));
+                paths.push(self.pattern_fill(vec![points.to_vec()], &mut options));
             }
+            fill_region = vec![points.to_vec()];
         }
         if options.stroke.is_some() {
             paths.push(outline);
         }
-        self.d("polygon".to_owned(), &paths, &Some(options))
+        self.d("polygon".to_owned(), &paths, fill_region, &Some(options))
     }
 
-    fn path(&self, d: String, options: &Option<DrawOptions>) -> RoughlyDrawable<OpSet<F>>
+    fn regular_polygon(
+        &self,
+        cx: F,
+        cy: F,
+        r: F,
+        sides: usize,
+        rotation: F,
+        options: &Option<DrawOptions>,
+    ) -> RoughlyDrawable<OpSet<F>>
     where
         F: RealNumber + Display,
     {
-        let mut options = options.clone().unwrap_or(self.default_options.clone());
+        let points = regular_polygon_points(cx, cy, r, sides, rotation);
+        let mut shape = self.polygon(&points, options);
+        shape.shape = "regular_polygon".into();
+        shape
+    }
+
+    fn star(
+        &self,
+        cx: F,
+        cy: F,
+        outer_r: F,
+        inner_r: F,
+        points: usize,
+        options: &Option<DrawOptions>,
+    ) -> RoughlyDrawable<OpSet<F>>
+    where
+        F: RealNumber + Display,
+    {
+        let points = star_points(cx, cy, outer_r, inner_r, points);
+        let mut shape = self.polygon(&points, options);
+        shape.shape = "star".into();
+        shape
+    }
+
+    fn superellipse(
+        &self,
+        cx: F,
+        cy: F,
+        width: F,
+        height: F,
+        n: F,
+        options: &Option<DrawOptions>,
+    ) -> RoughlyDrawable<OpSet<F>>
+    where
+        F: RealNumber,
+    {
         let mut paths = vec![];
-        if d.is_empty() {
-            self.d("path".to_owned(), &paths, &Some(options))
-        } else {
-            let simplified = options.simplification.map(|a| a < 1.0).unwrap_or(false);
-            let distance = if simplified {
-                _c::<F>(4.0) - _c::<F>(4.0) * _c::<F>(options.simplification.unwrap())
+        let mut options = options
+            .clone()
+            .unwrap_or_else(|| self.default_options.clone());
+        let params = generate_ellipse_params(width, height, &mut options);
+        let response = superellipse_with_params(cx, cy, n, &mut options, &params);
+        let mut fill_region = vec![];
+        if options.fill.is_some() {
+            if options.fill_style == Some(FillStyle::Solid) {
+                let mut shape = superellipse_with_params(cx, cy, n, &mut options, &params).opset;
+                shape.op_set_type = OpSetType::FillPath;
+                paths.push(shape);
             } else {
-                (_c::<F>(1.0) + _c::<F>(options.roughness.unwrap_or(1.0))) / _c::<F>(2.0)
-            };
-
-            let sets = points_on_path(d.clone(), Some(_c(1.0)), Some(distance));
-            if options.fill.is_some() {
-                if options.fill_style == Some(FillStyle::Solid) {
-                    paths.push(solid_fill_polygon(&sets, &mut options));
-                } else {
-                    paths.push(pattern_fill_polygons(sets.clone(), &mut options));
-                }
+                paths
+                    .push(self.pattern_fill(vec![response.estimated_points.clone()], &mut options));
             }
+            fill_region = vec![response.estimated_points];
+        }
+        if options.stroke.is_some() {
+            paths.push(response.opset);
+        }
+        self.d(
+            "superellipse".to_owned(),
+            &paths,
+            fill_region,
+            &Some(options),
+        )
+    }
 
-            if options.stroke.is_some() {
-                if simplified {
-                    sets.iter()
-                        .for_each(|s| paths.push(linear_path(s, false, &mut options)));
-                } else {
-                    paths.push(svg_path(d, &mut options));
-                }
-            }
+    fn path(&self, d: String, options: &Option<DrawOptions>) -> RoughlyDrawable<OpSet<F>>
+    where
+        F: RealNumber + Display,
+    {
+        let segments: Vec<PathSegment> = PathParser::from(d.as_ref()).flatten().collect();
+        self.path_segments(&segments, options)
+    }
 
-            self.d("path".to_owned(), &paths, &Some(options))
-        }
+    fn path_from_builder(
+        &self,
+        path: &RoughPathBuilder<F>,
+        options: &Option<DrawOptions>,
+    ) -> RoughlyDrawable<OpSet<F>> {
+        self.path_segments(path.segments(), options)
+    }
+
+    fn path_elements(
+        &self,
+        elements: impl IntoIterator<Item = PathEl>,
+        options: &Option<DrawOptions>,
+    ) -> RoughlyDrawable<OpSet<F>> {
+        let segments: Vec<PathSegment> =
+            elements.into_iter().map(path_element_to_segment).collect();
+        self.path_segments(&segments, options)
     }
+
+    fn try_path(
+        &self,
+        d: String,
+        options: &Option<DrawOptions>,
+    ) -> Result<RoughlyDrawable<OpSet<F>>, Error> {
+        self.try_path_segments(&parse_path_segments(&d)?, options)
+    }
+
+    fn try_path_from_builder(
+        &self,
+        path: &RoughPathBuilder<F>,
+        options: &Option<DrawOptions>,
+    ) -> Result<RoughlyDrawable<OpSet<F>>, Error> {
+        self.try_path_segments(path.segments(), options)
+    }
+
+    fn try_path_elements(
+        &self,
+        elements: impl IntoIterator<Item = PathEl>,
+        options: &Option<DrawOptions>,
+    ) -> Result<RoughlyDrawable<OpSet<F>>, Error> {
+        let segments: Vec<PathSegment> =
+            elements.into_iter().map(path_element_to_segment).collect();
+        self.try_path_segments(&segments, options)
+    }
+}
+
+/// The pie slice covered by a closed arc, sampled without roughness.
+fn arc_region<F: RealNumber>(x: F, y: F, width: F, height: F, start: F, stop: F) -> Vec<Point2<F>> {
+    let rx = (width / _c(2.0)).abs();
+    let ry = (height / _c(2.0)).abs();
+    let steps = 36;
+    let mut points: Vec<Point2<F>> = (0..=steps)
+        .map(|i| {
+            let angle = start + (stop - start) * _c::<F>(i as f32) / _c::<F>(steps as f32);
+            Point2::new(x + rx * angle.cos(), y + ry * angle.sin())
+        })
+        .collect();
+    points.push(Point2::new(x, y));
+    points
 }
@@ -1,14 +1,43 @@
 pub mod generator;
 
+use crate::error::Error;
+use crate::graphics::arrow::ArrowOptions;
 use crate::graphics::drawable::{DrawOptions, Drawable, OpSetTrait};
-use nalgebra::Point2;
+use crate::graphics::filler::PatternFiller;
+use crate::graphics::path_builder::RoughPathBuilder;
+#[cfg(feature = "text")]
+use crate::graphics::text::{text_path, Font, TextOptions};
+use nalgebra::{ComplexField, Point2};
 use nalgebra_glm::RealNumber;
+use piet::kurbo::{Affine, BezPath, PathEl, Shape};
+use std::collections::HashMap;
 use std::fmt::Display;
 use std::marker::PhantomData;
 
+/// Tolerance used when converting curved kurbo shapes into path elements.
+pub const SHAPE_TOLERANCE: f64 = 0.1;
+
 // Data types
+type FillerRegistry<F> =
+    HashMap<String, Box<dyn PatternFiller<F, Vec<Vec<Point2<F>>>> + Send + Sync>>;
+
+/// The space a length or angle option is measured in when the generator has
+/// a transform.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TransformSpace {
+    /// Measured before the transform, so it is scaled and rotated with the shape.
+    #[default]
+    Local,
+    /// Measured after the transform, on the output.
+    Device,
+}
+
 pub struct Generator<OpSetT: OpSetTrait> {
     default_options: DrawOptions,
+    fillers: FillerRegistry<OpSetT::F>,
+    transform: Affine,
+    hachure_space: TransformSpace,
+    stroke_space: TransformSpace,
     phantom_data_opsett: PhantomData<OpSetT>,
 }
 
@@ -32,6 +61,19 @@ where
         options: &Option<DrawOptions>,
     ) -> OutputDrawable;
 
+    /// A rectangle with rounded corners. `radii` are ordered top-left,
+    /// top-right, bottom-right and bottom-left, and are scaled down when the
+    /// corners on a side would overlap.
+    fn rounded_rectangle(
+        &self,
+        x: F,
+        y: F,
+        width: F,
+        height: F,
+        radii: [F; 4],
+        options: &Option<DrawOptions>,
+    ) -> OutputDrawable;
+
     fn ellipse(
         &self,
         x: F,
@@ -52,6 +94,43 @@ where
 
     fn polygon(&self, points: &[Point2<F>], options: &Option<DrawOptions>) -> OutputDrawable;
 
+    /// A regular polygon inscribed in a circle of radius `r`. With zero
+    /// `rotation` (in radians) the first vertex points up.
+    fn regular_polygon(
+        &self,
+        cx: F,
+        cy: F,
+        r: F,
+        sides: usize,
+        rotation: F,
+        options: &Option<DrawOptions>,
+    ) -> OutputDrawable;
+
+    /// A star with `points` tips at `outer_r` and inner corners at `inner_r`.
+    /// The first tip points up.
+    fn star(
+        &self,
+        cx: F,
+        cy: F,
+        outer_r: F,
+        inner_r: F,
+        points: usize,
+        options: &Option<DrawOptions>,
+    ) -> OutputDrawable;
+
+    /// A superellipse `|x / a|^n + |y / b|^n = 1` with `a` and `b` half of
+    /// `width` and `height`. `n` of 2 is an ellipse, larger values approach a
+    /// rectangle.
+    fn superellipse(
+        &self,
+        cx: F,
+        cy: F,
+        width: F,
+        height: F,
+        n: F,
+        options: &Option<DrawOptions>,
+    ) -> OutputDrawable;
+
     fn arc(
         &self,
         x: F,
@@ -83,5 +162,787 @@ where
 
     fn curve(&self, points: &[Point2<F>], options: &Option<DrawOptions>) -> OutputDrawable;
 
+    /// A straight arrow from `start` to `end` with the heads set in `arrow`.
+    fn arrow(
+        &self,
+        start: Point2<F>,
+        end: Point2<F>,
+        arrow: &ArrowOptions,
+        options: &Option<DrawOptions>,
+    ) -> OutputDrawable;
+
+    /// An arrow along a cubic Bézier curve. The heads follow the tangent at
+    /// either end.
+    fn curved_arrow(
+        &self,
+        start: Point2<F>,
+        cp1: Point2<F>,
+        cp2: Point2<F>,
+        end: Point2<F>,
+        arrow: &ArrowOptions,
+        options: &Option<DrawOptions>,
+    ) -> OutputDrawable;
+
     fn path(&self, svg_path: String, options: &Option<DrawOptions>) -> OutputDrawable;
+
+    /// Sketches a path given as kurbo path elements, producing the same sketch
+    /// as [`Self::path`] with the equivalent SVG path data.
+    fn path_elements(
+        &self,
+        elements: impl IntoIterator<Item = PathEl>,
+        options: &Option<DrawOptions>,
+    ) -> OutputDrawable;
+
+    fn path_from_builder(
+        &self,
+        path: &RoughPathBuilder<F>,
+        options: &Option<DrawOptions>,
+    ) -> OutputDrawable;
+
+    fn bez_path(&self, path: &BezPath, options: &Option<DrawOptions>) -> OutputDrawable {
+        self.path_elements(path.iter(), options)
+    }
+
+    /// Sketches the outline of any kurbo shape as a path. Curved shapes are
+    /// approximated with Bézier segments within [`SHAPE_TOLERANCE`].
+    fn shape(&self, shape: &impl Shape, options: &Option<DrawOptions>) -> OutputDrawable {
+        self.path_elements(shape.path_elements(SHAPE_TOLERANCE), options)
+    }
+
+    /// Sketches `text` set in `font` as one path, with `(x, y)` at the left
+    /// end of the first baseline.
+    #[cfg(feature = "text")]
+    fn text(
+        &self,
+        font: &Font,
+        text: &str,
+        x: F,
+        y: F,
+        text_options: &TextOptions,
+        options: &Option<DrawOptions>,
+    ) -> OutputDrawable {
+        self.path_from_builder(&text_path(font, text, x, y, text_options), options)
+    }
+
+    // Fallible variants of the shapes above. They reject input that cannot be
+    // drawn with an `Error` instead of producing garbage or panicking.
+
+    fn try_line(
+        &self,
+        x1: F,
+        y1: F,
+        x2: F,
+        y2: F,
+        options: &Option<DrawOptions>,
+    ) -> Result<OutputDrawable, Error> {
+        check_finite("line", &[x1, y1, x2, y2])?;
+        Ok(self.line(x1, y1, x2, y2, options))
+    }
+
+    fn try_rectangle(
+        &self,
+        x: F,
+        y: F,
+        width: F,
+        height: F,
+        options: &Option<DrawOptions>,
+    ) -> Result<OutputDrawable, Error> {
+        check_finite("rectangle", &[x, y, width, height])?;
+        Ok(self.rectangle(x, y, width, height, options))
+    }
+
+    fn try_rounded_rectangle(
+        &self,
+        x: F,
+        y: F,
+        width: F,
+        height: F,
+        radii: [F; 4],
+        options: &Option<DrawOptions>,
+    ) -> Result<OutputDrawable, Error> {
+        check_finite("rounded_rectangle", &[x, y, width, height])?;
+        check_finite("rounded_rectangle", &radii)?;
+        Ok(self.rounded_rectangle(x, y, width, height, radii, options))
+    }
+
+    fn try_ellipse(
+        &self,
+        x: F,
+        y: F,
+        width: F,
+        height: F,
+        options: &Option<DrawOptions>,
+    ) -> Result<OutputDrawable, Error> {
+        check_finite("ellipse", &[x, y, width, height])?;
+        Ok(self.ellipse(x, y, width, height, options))
+    }
+
+    fn try_circle(
+        &self,
+        x: F,
+        y: F,
+        diameter: F,
+        options: &Option<DrawOptions>,
+    ) -> Result<OutputDrawable, Error> {
+        check_finite("circle", &[x, y, diameter])?;
+        Ok(self.circle(x, y, diameter, options))
+    }
+
+    fn try_linear_path(
+        &self,
+        points: &[Point2<F>],
+        close: bool,
+        options: &Option<DrawOptions>,
+    ) -> Result<OutputDrawable, Error> {
+        check_points("linear_path", points, 2)?;
+        Ok(self.linear_path(points, close, options))
+    }
+
+    fn try_polygon(
+        &self,
+        points: &[Point2<F>],
+        options: &Option<DrawOptions>,
+    ) -> Result<OutputDrawable, Error> {
+        check_points("polygon", points, 3)?;
+        Ok(self.polygon(points, options))
+    }
+
+    fn try_regular_polygon(
+        &self,
+        cx: F,
+        cy: F,
+        r: F,
+        sides: usize,
+        rotation: F,
+        options: &Option<DrawOptions>,
+    ) -> Result<OutputDrawable, Error> {
+        check_finite("regular_polygon", &[cx, cy, r, rotation])?;
+        check_count("regular_polygon", "sides", sides, 3)?;
+        Ok(self.regular_polygon(cx, cy, r, sides, rotation, options))
+    }
+
+    fn try_star(
+        &self,
+        cx: F,
+        cy: F,
+        outer_r: F,
+        inner_r: F,
+        points: usize,
+        options: &Option<DrawOptions>,
+    ) -> Result<OutputDrawable, Error> {
+        check_finite("star", &[cx, cy, outer_r, inner_r])?;
+        check_count("star", "points", points, 2)?;
+        Ok(self.star(cx, cy, outer_r, inner_r, points, options))
+    }
+
+    fn try_superellipse(
+        &self,
+        cx: F,
+        cy: F,
+        width: F,
+        height: F,
+        n: F,
+        options: &Option<DrawOptions>,
+    ) -> Result<OutputDrawable, Error> {
+        check_finite("superellipse", &[cx, cy, width, height, n])?;
+        if n <= F::zero() {
+            return Err(Error::DegenerateInput(
+                "superellipse exponent must be positive".into(),
+            ));
+        }
+        Ok(self.superellipse(cx, cy, width, height, n, options))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn try_arc(
+        &self,
+        x: F,
+        y: F,
+        width: F,
+        height: F,
+        start: F,
+        stop: F,
+        closed: bool,
+        options: &Option<DrawOptions>,
+    ) -> Result<OutputDrawable, Error> {
+        check_finite("arc", &[x, y, width, height, start, stop])?;
+        Ok(self.arc(x, y, width, height, start, stop, closed, options))
+    }
+
+    fn try_bezier_quadratic(
+        &self,
+        start: Point2<F>,
+        cp: Point2<F>,
+        end: Point2<F>,
+        options: &Option<DrawOptions>,
+    ) -> Result<OutputDrawable, Error> {
+        check_points("bezier_quadratic", &[start, cp, end], 3)?;
+        Ok(self.bezier_quadratic(start, cp, end, options))
+    }
+
+    fn try_bezier_cubic(
+        &self,
+        start: Point2<F>,
+        cp1: Point2<F>,
+        cp2: Point2<F>,
+        end: Point2<F>,
+        options: &Option<DrawOptions>,
+    ) -> Result<OutputDrawable, Error> {
+        check_points("bezier_cubic", &[start, cp1, cp2, end], 4)?;
+        Ok(self.bezier_cubic(start, cp1, cp2, end, options))
+    }
+
+    fn try_curve(
+        &self,
+        points: &[Point2<F>],
+        options: &Option<DrawOptions>,
+    ) -> Result<OutputDrawable, Error> {
+        check_points("curve", points, 2)?;
+        Ok(self.curve(points, options))
+    }
+
+    fn try_arrow(
+        &self,
+        start: Point2<F>,
+        end: Point2<F>,
+        arrow: &ArrowOptions,
+        options: &Option<DrawOptions>,
+    ) -> Result<OutputDrawable, Error> {
+        check_points("arrow", &[start, end], 2)?;
+        check_head_size("arrow", arrow)?;
+        Ok(self.arrow(start, end, arrow, options))
+    }
+
+    fn try_curved_arrow(
+        &self,
+        start: Point2<F>,
+        cp1: Point2<F>,
+        cp2: Point2<F>,
+        end: Point2<F>,
+        arrow: &ArrowOptions,
+        options: &Option<DrawOptions>,
+    ) -> Result<OutputDrawable, Error> {
+        check_points("curved_arrow", &[start, cp1, cp2, end], 4)?;
+        check_head_size("curved_arrow", arrow)?;
+        Ok(self.curved_arrow(start, cp1, cp2, end, arrow, options))
+    }
+
+    /// Draws `svg_path` after checking that the whole string is valid path
+    /// data, where [`Self::path`] silently drops everything after the first
+    /// malformed command, and reports segments that cannot be flattened or
+    /// converted to `F`.
+    fn try_path(
+        &self,
+        svg_path: String,
+        options: &Option<DrawOptions>,
+    ) -> Result<OutputDrawable, Error>;
+
+    /// Like [`Self::path_elements`], but reports elements that cannot be
+    /// flattened or converted to `F` instead of drawing nothing.
+    fn try_path_elements(
+        &self,
+        elements: impl IntoIterator<Item = PathEl>,
+        options: &Option<DrawOptions>,
+    ) -> Result<OutputDrawable, Error>;
+
+    fn try_path_from_builder(
+        &self,
+        path: &RoughPathBuilder<F>,
+        options: &Option<DrawOptions>,
+    ) -> Result<OutputDrawable, Error>;
+
+    fn try_bez_path(
+        &self,
+        path: &BezPath,
+        options: &Option<DrawOptions>,
+    ) -> Result<OutputDrawable, Error> {
+        self.try_path_elements(path.iter(), options)
+    }
+
+    fn try_shape(
+        &self,
+        shape: &impl Shape,
+        options: &Option<DrawOptions>,
+    ) -> Result<OutputDrawable, Error> {
+        self.try_path_elements(shape.path_elements(SHAPE_TOLERANCE), options)
+    }
+
+    #[cfg(feature = "text")]
+    fn try_text(
+        &self,
+        font: &Font,
+        text: &str,
+        x: F,
+        y: F,
+        text_options: &TextOptions,
+        options: &Option<DrawOptions>,
+    ) -> Result<OutputDrawable, Error> {
+        self.try_path_from_builder(&text_path(font, text, x, y, text_options), options)
+    }
+}
+
+fn check_finite<F: RealNumber>(shape: &str, values: &[F]) -> Result<(), Error> {
+    if values.iter().all(|v| ComplexField::is_finite(v)) {
+        Ok(())
+    } else {
+        Err(Error::DegenerateInput(format!(
+            "{} has a non finite coordinate",
+            shape
+        )))
+    }
+}
+
+fn check_head_size(shape: &str, arrow: &ArrowOptions) -> Result<(), Error> {
+    if arrow.head_size.is_finite() && arrow.head_size >= 0.0 {
+        Ok(())
+    } else {
+        Err(Error::DegenerateInput(format!(
+            "{} has an invalid head size {}",
+            shape, arrow.head_size
+        )))
+    }
+}
+
+fn check_count(shape: &str, name: &str, count: usize, min_count: usize) -> Result<(), Error> {
+    if count < min_count {
+        return Err(Error::DegenerateInput(format!(
+            "{} needs at least {} {}, got {}",
+            shape, min_count, name, count
+        )));
+    }
+    Ok(())
+}
+
+fn check_points<F: RealNumber>(
+    shape: &str,
+    points: &[Point2<F>],
+    min_points: usize,
+) -> Result<(), Error> {
+    if points.len() < min_points {
+        return Err(Error::DegenerateInput(format!(
+            "{} needs at least {} points, got {}",
+            shape,
+            min_points,
+            points.len()
+        )));
+    }
+    points
+        .iter()
+        .try_for_each(|p| check_finite(shape, &[p.x, p.y]))
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::relative_eq;
+    use nalgebra::{Point2, Vector2};
+    use piet::kurbo::{Affine, BezPath, Rect};
+    use std::f64::consts::FRAC_PI_2;
+
+    use super::{Generator, RoughlyDrawableMakable, TransformSpace};
+    use crate::error::Error;
+    use crate::graphics::arrow::{ArrowHead, ArrowOptions, ArrowOptionsBuilder};
+    use crate::graphics::drawable::{DrawOptions, DrawOptionsBuilder};
+    use crate::graphics::drawable_ops::{Op, OpSet, OpSetType, OpType};
+    use crate::graphics::filler::stipple_filler::{Shading, StippleFiller};
+    use crate::graphics::filler::{polygon_hachure_lines, PatternFiller};
+    use crate::graphics::paint::FillStyle;
+
+    #[test]
+    fn try_path_reports_offset_of_bad_data() {
+        let generator = Generator::<OpSet<f64>>::default();
+        let d = "M 10 10 L 20 20 L 30 oops";
+        match generator.try_path(d.into(), &None) {
+            Err(Error::InvalidPathData { offset, .. }) => assert_eq!(&d[offset..], "oops"),
+            Err(e) => panic!("unexpected error {}", e),
+            Ok(_) => panic!("bad path data was accepted"),
+        }
+        assert!(generator.try_path("M 10 10 L 20 20".into(), &None).is_ok());
+    }
+
+    #[test]
+    fn try_variants_reject_degenerate_input() {
+        let generator = Generator::<OpSet<f32>>::default();
+        assert!(matches!(
+            generator.try_line(0.0, f32::NAN, 1.0, 1.0, &None),
+            Err(Error::DegenerateInput(_))
+        ));
+        assert!(matches!(
+            generator.try_polygon(&[Point2::new(0.0, 0.0), Point2::new(1.0, 1.0)], &None),
+            Err(Error::DegenerateInput(_))
+        ));
+        assert!(generator.try_rectangle(0.0, 0.0, 10.0, 10.0, &None).is_ok());
+    }
+
+    #[test]
+    fn try_path_reports_arcs_that_flatten_out_of_range() {
+        // every number fits in f32, but the far side of the arc does not
+        let generator = Generator::<OpSet<f32>>::default();
+        assert!(matches!(
+            generator.try_path("M 0 0 A 2e38 2e38 0 1 1 2e38 0".into(), &None),
+            Err(Error::NumericConversion(_))
+        ));
+    }
+
+    #[test]
+    fn f32_overflow_is_a_numeric_conversion_error() {
+        let generator = Generator::<OpSet<f32>>::default();
+        assert!(matches!(
+            generator.try_path("M 0 0 L 1e39 0".into(), &None),
+            Err(Error::NumericConversion(_))
+        ));
+        let mut path = BezPath::new();
+        path.move_to((0.0, 0.0));
+        path.line_to((1e39, 0.0));
+        assert!(matches!(
+            generator.try_bez_path(&path, &None),
+            Err(Error::NumericConversion(_))
+        ));
+        // the lenient variant draws nothing rather than a broken sketch
+        assert!(generator.bez_path(&path, &None).opsets.is_empty());
+
+        let mut path = BezPath::new();
+        path.move_to((0.0, 0.0));
+        path.line_to((f64::NAN, 0.0));
+        assert!(matches!(
+            generator.try_bez_path(&path, &None),
+            Err(Error::DegenerateInput(_))
+        ));
+    }
+
+    #[test]
+    fn lenient_variants_do_not_panic_on_bad_input() {
+        let options = DrawOptionsBuilder::default()
+            .fill(palette::Srgba::new(0.0, 0.0, 0.0, 1.0))
+            .build()
+            .unwrap();
+        let generator = Generator::<OpSet<f64>>::new(options);
+        let path = generator.path("M 10 10 L 50 10 L 30 x 40".into(), &None);
+        assert!(!path.opsets.is_empty());
+        let curve = generator.curve(&[], &None);
+        assert!(curve.opsets.iter().all(|set| set.ops.is_empty()));
+    }
+
+    #[test]
+    fn bez_path_matches_string_path() {
+        let options = DrawOptionsBuilder::default()
+            .fill(palette::Srgba::new(0.0, 0.0, 0.0, 1.0))
+            .build()
+            .unwrap();
+        let generator = Generator::<OpSet<f64>>::new(options);
+        let d = "M10 10 Q40 80 90 10 C120 -20 150 60 180 30 L200 100 Z";
+        let bez_path = BezPath::from_svg(d).unwrap();
+        assert_eq!(
+            generator.bez_path(&bez_path, &None).opsets,
+            generator.path(d.into(), &None).opsets
+        );
+    }
+
+    #[test]
+    fn shape_matches_string_path() {
+        let generator = Generator::<OpSet<f32>>::default();
+        let rect = Rect::new(0.0, 0.0, 10.0, 5.0);
+        assert_eq!(
+            generator.shape(&rect, &None).opsets,
+            generator
+                .path("M0 0 L10 0 L10 5 L0 5 Z".into(), &None)
+                .opsets
+        );
+    }
+
+    #[test]
+    fn rounded_rectangle_fills_stay_inside_corners() {
+        let styles = [
+            FillStyle::Hachure,
+            FillStyle::ZigZag,
+            FillStyle::CrossHatch,
+            FillStyle::Dots,
+            FillStyle::Dashed,
+            FillStyle::ZigZagLine,
+        ];
+        for style in styles {
+            let options = DrawOptionsBuilder::default()
+                .fill(palette::Srgba::new(0.0, 0.0, 0.0, 1.0))
+                .fill_style(style.clone())
+                .build()
+                .unwrap();
+            let generator = Generator::<OpSet<f64>>::new(options);
+            let drawable = generator.rounded_rectangle(0.0, 0.0, 100.0, 100.0, [40.0; 4], &None);
+            let fill = &drawable.opsets[0];
+            assert_eq!(fill.op_set_type, OpSetType::FillSketch);
+            assert!(!fill.ops.is_empty(), "{:?} produced no fill", style);
+            let in_corner = |p: &Point2<f64>| {
+                let (x, y) = (p.x.min(100.0 - p.x), p.y.min(100.0 - p.y));
+                x < 8.0 && y < 8.0
+            };
+            for polyline in fill.to_polylines(0.5) {
+                assert!(
+                    !polyline.iter().any(in_corner),
+                    "{:?} fills a corner",
+                    style
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn badge_shapes() {
+        let options = DrawOptionsBuilder::default()
+            .fill(palette::Srgba::new(0.0, 0.0, 0.0, 1.0))
+            .build()
+            .unwrap();
+        let generator = Generator::<OpSet<f64>>::new(options);
+        let hexagon = generator.regular_polygon(50.0, 50.0, 40.0, 6, 0.0, &None);
+        assert_eq!(hexagon.fill_region[0].len(), 6);
+        let star = generator.star(50.0, 50.0, 40.0, 15.0, 5, &None);
+        assert_eq!(star.fill_region[0].len(), 10);
+        let squircle = generator.superellipse(50.0, 50.0, 80.0, 80.0, 4.0, &None);
+        assert_eq!(squircle.opsets[0].op_set_type, OpSetType::FillSketch);
+        assert_eq!(squircle.opsets[1].op_set_type, OpSetType::Path);
+
+        assert!(matches!(
+            generator.try_regular_polygon(0.0, 0.0, 10.0, 2, 0.0, &None),
+            Err(Error::DegenerateInput(_))
+        ));
+        assert!(matches!(
+            generator.try_superellipse(0.0, 0.0, 10.0, 10.0, 0.0, &None),
+            Err(Error::DegenerateInput(_))
+        ));
+        assert!(generator.try_star(0.0, 0.0, 10.0, 4.0, 5, &None).is_ok());
+    }
+
+    #[test]
+    fn arrows_with_filled_heads() {
+        let generator = Generator::<OpSet<f64>>::default();
+        let arrow = ArrowOptionsBuilder::default()
+            .start(ArrowHead::Bar)
+            .end(ArrowHead::Triangle { filled: true })
+            .build()
+            .unwrap();
+        let (start, end) = (Point2::new(0.0, 0.0), Point2::new(100.0, 0.0));
+        let drawable = generator.arrow(start, end, &arrow, &None);
+        assert_eq!(drawable.opsets.len(), 2);
+        assert_eq!(drawable.opsets[0].op_set_type, OpSetType::FillPath);
+        assert_eq!(drawable.opsets[1].op_set_type, OpSetType::Path);
+        assert_eq!(drawable.options.fill, drawable.options.stroke);
+        assert_eq!(drawable.fill_region.len(), 1);
+        assert!(drawable.fill_region[0]
+            .iter()
+            .all(|p| p.x > 50.0 && p.x <= 100.0 + 1.0e-9));
+
+        let colourless = DrawOptions {
+            stroke: None,
+            fill: None,
+            ..DrawOptions::default()
+        };
+        let bare = generator.arrow(start, end, &arrow, &Some(colourless));
+        assert!(bare.opsets.is_empty());
+        assert!(bare.fill_region.is_empty());
+
+        let curved = generator.curved_arrow(
+            start,
+            Point2::new(30.0, 50.0),
+            Point2::new(70.0, 50.0),
+            end,
+            &ArrowOptions::default(),
+            &None,
+        );
+        assert_eq!(curved.opsets.len(), 1);
+        assert!(curved.options.fill.is_none());
+
+        let bad = ArrowOptionsBuilder::default()
+            .head_size(-1.0)
+            .build()
+            .unwrap();
+        assert!(matches!(
+            generator.try_arrow(start, end, &bad, &None),
+            Err(Error::DegenerateInput(_))
+        ));
+    }
+
+    /// Draws the plain hachure lines, without any roughness.
+    struct StraightHachure;
+
+    impl PatternFiller<f64, Vec<Vec<Point2<f64>>>> for StraightHachure {
+        fn fill_polygons(
+            &self,
+            mut polygon_list: Vec<Vec<Point2<f64>>>,
+            o: &mut DrawOptions,
+        ) -> OpSet<f64> {
+            let ops = polygon_hachure_lines(&mut polygon_list, o)
+                .iter()
+                .flat_map(|line| {
+                    [
+                        Op {
+                            op: OpType::Move,
+                            data: vec![line.start_point.x, line.start_point.y],
+                        },
+                        Op {
+                            op: OpType::LineTo,
+                            data: vec![line.end_point.x, line.end_point.y],
+                        },
+                    ]
+                })
+                .collect();
+            OpSet {
+                op_set_type: OpSetType::FillSketch,
+                ops,
+                size: None,
+                path: None,
+            }
+        }
+    }
+
+    #[test]
+    fn custom_fill_styles_use_registered_fillers() {
+        let mut generator = Generator::<OpSet<f64>>::default();
+        generator.register_filler("straight", StraightHachure);
+        let options = |name: &str| {
+            Some(
+                DrawOptionsBuilder::default()
+                    .fill(palette::Srgba::new(0.0, 0.0, 0.0, 1.0))
+                    .fill_style(FillStyle::Custom(name.into()))
+                    .hachure_angle(0.0)
+                    .hachure_gap(10.0)
+                    .build()
+                    .unwrap(),
+            )
+        };
+
+        let square = generator.rectangle(0.0, 0.0, 100.0, 100.0, &options("straight"));
+        let fill = &square.opsets[0];
+        assert_eq!(fill.op_set_type, OpSetType::FillSketch);
+        assert_eq!(fill.ops.len() % 2, 0);
+        // the lines are vertical and untouched by roughness
+        for pair in fill.ops.chunks(2) {
+            assert!(relative_eq!(
+                pair[0].data[0],
+                pair[1].data[0],
+                epsilon = 1.0e-9
+            ));
+        }
+
+        let fallback = generator.rectangle(0.0, 0.0, 100.0, 100.0, &options("unknown"));
+        assert_eq!(fallback.opsets[0].op_set_type, OpSetType::FillSketch);
+        assert!(fallback.opsets[0]
+            .ops
+            .iter()
+            .any(|op| op.op == OpType::BCurveTo));
+    }
+
+    #[test]
+    fn configured_stipple_fillers_are_registered_as_custom_styles() {
+        let mut generator = Generator::<OpSet<f64>>::default();
+        generator.register_filler(
+            "shaded",
+            StippleFiller::new()
+                .with_density(0.5)
+                .with_shading(Shading::Gradient(Vector2::new(1.0, 0.0))),
+        );
+        let options = |style: FillStyle| {
+            Some(
+                DrawOptionsBuilder::default()
+                    .fill(palette::Srgba::new(0.0, 0.0, 0.0, 1.0))
+                    .fill_style(style)
+                    .hachure_gap(4.0)
+                    .build()
+                    .unwrap(),
+            )
+        };
+        // each dot starts a new sketched ellipse at its centre's right edge
+        let starts = |style: FillStyle| {
+            let square = generator.rectangle(0.0, 0.0, 100.0, 100.0, &options(style));
+            let moves: Vec<f64> = square.opsets[0]
+                .ops
+                .iter()
+                .filter(|op| op.op == OpType::Move)
+                .map(|op| op.data[0])
+                .collect();
+            let left = moves.iter().filter(|x| **x < 50.0).count();
+            (left, moves.len() - left)
+        };
+
+        let (left, right) = starts(FillStyle::Custom("shaded".into()));
+        assert!(left * 2 < right);
+        let (uniform_left, uniform_right) = starts(FillStyle::Stipple);
+        assert!(left + right < uniform_left + uniform_right);
+    }
+
+    #[test]
+    fn generators_with_custom_fillers_are_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>(_: &T) {}
+        let mut generator = Generator::<OpSet<f64>>::default();
+        generator.register_filler("straight", StraightHachure);
+        assert_send_sync(&generator);
+    }
+
+    #[test]
+    fn generator_transform_spaces() {
+        let mut generator = Generator::<OpSet<f64>>::default();
+        generator
+            .register_filler("straight", StraightHachure)
+            .set_transform(Affine::scale(2.0) * Affine::rotate(FRAC_PI_2));
+        let options = Some(
+            DrawOptionsBuilder::default()
+                .fill(palette::Srgba::new(0.0, 0.0, 0.0, 1.0))
+                .fill_style(FillStyle::Custom("straight".into()))
+                .hachure_angle(0.0)
+                .hachure_gap(10.0)
+                .stroke_width(1.0)
+                .build()
+                .unwrap(),
+        );
+        // (index of the coordinate lines share, distance between lines)
+        let hachure = |generator: &Generator<OpSet<f64>>| {
+            let square = generator.rectangle(0.0, 0.0, 50.0, 50.0, &options);
+            assert!(square
+                .fill_region
+                .iter()
+                .flatten()
+                .all(|p| p.x < 1.0e-9 && p.y > -1.0e-9));
+            // skips the first line, which only touches a corner
+            let ops = &square.opsets[0].ops[2..];
+            let axis = if relative_eq!(ops[0].data[0], ops[1].data[0], epsilon = 1.0e-9) {
+                0
+            } else {
+                1
+            };
+            let gap = (ops[2].data[axis] - ops[0].data[axis]).abs();
+            (axis, gap, square.options.stroke_width)
+        };
+
+        // local hachure turns and scales with the shape, widths scale too
+        let (axis, gap, width) = hachure(&generator);
+        assert_eq!(axis, 1);
+        assert!(relative_eq!(gap, 20.0, epsilon = 1.0e-9));
+        assert_eq!(width, Some(2.0));
+
+        generator
+            .set_hachure_space(TransformSpace::Device)
+            .set_stroke_space(TransformSpace::Device);
+        let (axis, gap, width) = hachure(&generator);
+        assert_eq!(axis, 0);
+        assert!(relative_eq!(gap, 10.0, epsilon = 1.0e-9));
+        assert_eq!(width, Some(1.0));
+    }
+
+    #[test]
+    fn bounds_and_hit_testing() {
+        let generator = Generator::<OpSet<f64>>::default();
+        let outline = generator.rectangle(10.0, 10.0, 50.0, 30.0, &None);
+        let bounds = outline.bounds().unwrap();
+        // roughness moves the lines by a few units at most
+        assert!(bounds.min.x < 10.0 && bounds.min.x > 4.0);
+        assert!(bounds.max.y > 40.0 && bounds.max.y < 46.0);
+        assert!(outline.hit_test(&Point2::new(10.0, 25.0), 3.0));
+        assert!(!outline.hit_test(&Point2::new(35.0, 25.0), 3.0));
+        assert!(!outline.hit_test(&Point2::new(100.0, 100.0), 3.0));
+
+        let filled = DrawOptionsBuilder::default()
+            .fill(palette::Srgba::new(0.0, 0.0, 0.0, 1.0))
+            .fill_style(FillStyle::Solid)
+            .build()
+            .unwrap();
+        let solid = generator.rectangle(10.0, 10.0, 50.0, 30.0, &Some(filled));
+        assert!(solid.hit_test(&Point2::new(35.0, 25.0), 0.0));
+    }
 }
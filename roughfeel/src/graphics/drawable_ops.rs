@@ -1,9 +1,16 @@
+use std::fmt::Display;
+
 use nalgebra::Point2;
 use nalgebra_glm::RealNumber;
+use piet::kurbo::Affine;
+use points_on_curve::points_on_bezier_curves;
 
+use super::_c;
 use super::drawable::OpSetTrait;
+use super::geometry::transform_point;
 
 #[derive(Clone, PartialEq, Debug, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum OpType {
     Move,
     BCurveTo,
@@ -11,14 +18,18 @@ pub enum OpType {
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum OpSetType {
     Path,
     FillPath,
     FillSketch,
+    /// The outline of a brush stroke, filled with the stroke colour.
+    BrushPath,
 }
 
 /// A unified data structure that stores all drawing operations
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Op<F: RealNumber> {
     //Paco: SIMD?
     pub op: OpType,
@@ -26,6 +37,7 @@ pub struct Op<F: RealNumber> {
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct OpSet<F: RealNumber> {
     pub op_set_type: OpSetType,
     pub ops: Vec<Op<F>>,
@@ -33,6 +45,292 @@ pub struct OpSet<F: RealNumber> {
     pub path: Option<String>,
 }
 
+/// An axis aligned box from `min` to `max`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Bounds<F: RealNumber> {
+    pub min: Point2<F>,
+    pub max: Point2<F>,
+}
+
+impl<F: RealNumber> Bounds<F> {
+    fn point(p: Point2<F>) -> Self {
+        Bounds { min: p, max: p }
+    }
+
+    /// The smallest box containing `points`. `None` when there are none.
+    pub fn of_points<'a>(points: impl IntoIterator<Item = &'a Point2<F>>) -> Option<Bounds<F>> {
+        let mut points = points.into_iter();
+        let mut bounds = Bounds::point(*points.next()?);
+        points.for_each(|p| bounds.include(p));
+        Some(bounds)
+    }
+
+    fn include(&mut self, p: &Point2<F>) {
+        self.min = Point2::new(self.min.x.min(p.x), self.min.y.min(p.y));
+        self.max = Point2::new(self.max.x.max(p.x), self.max.y.max(p.y));
+    }
+
+    /// The smallest box containing both boxes.
+    pub fn union(&self, other: &Bounds<F>) -> Bounds<F> {
+        let mut union = *self;
+        union.include(&other.min);
+        union.include(&other.max);
+        union
+    }
+
+    /// The box grown by `margin` on every side.
+    pub fn inflate(&self, margin: F) -> Bounds<F> {
+        Bounds {
+            min: Point2::new(self.min.x - margin, self.min.y - margin),
+            max: Point2::new(self.max.x + margin, self.max.y + margin),
+        }
+    }
+
+    pub fn width(&self) -> F {
+        self.max.x - self.min.x
+    }
+
+    pub fn height(&self) -> F {
+        self.max.y - self.min.y
+    }
+}
+
+impl OpSetType {
+    /// Whether the ops outline an area to fill rather than lines to stroke.
+    pub fn is_filled(&self) -> bool {
+        matches!(self, OpSetType::FillPath | OpSetType::BrushPath)
+    }
+}
+
 impl<F: RealNumber> OpSetTrait for OpSet<F> {
     type F = F;
 }
+
+impl<F: RealNumber> Op<F> {
+    /// Maps every point of the op through `affine`.
+    pub fn transform(&mut self, affine: &Affine) {
+        for xy in self.data.chunks_exact_mut(2) {
+            let p = transform_point(affine, &Point2::new(xy[0], xy[1]));
+            xy[0] = p.x;
+            xy[1] = p.y;
+        }
+    }
+}
+
+impl<F: RealNumber> OpSet<F> {
+    /// The exact bounds of the ops, including the extrema of Bézier curves
+    /// rather than their control points. `None` when there are no ops.
+    pub fn bounds(&self) -> Option<Bounds<F>> {
+        let mut bounds: Option<Bounds<F>> = None;
+        let mut current: Option<Point2<F>> = None;
+        let mut include = |p: Point2<F>| match bounds.as_mut() {
+            Some(b) => b.include(&p),
+            None => bounds = Some(Bounds::point(p)),
+        };
+        for item in self.ops.iter() {
+            let end = match item.op {
+                OpType::Move | OpType::LineTo => Point2::new(item.data[0], item.data[1]),
+                OpType::BCurveTo => {
+                    let curve = [
+                        current.unwrap_or_else(|| Point2::new(item.data[0], item.data[1])),
+                        Point2::new(item.data[0], item.data[1]),
+                        Point2::new(item.data[2], item.data[3]),
+                        Point2::new(item.data[4], item.data[5]),
+                    ];
+                    include(curve[0]);
+                    for t in cubic_extrema(&curve) {
+                        include(cubic_point(&curve, t));
+                    }
+                    curve[3]
+                }
+            };
+            include(end);
+            current = Some(end);
+        }
+        bounds
+    }
+
+    /// Maps every op through `affine`. Since Bézier curves are preserved by
+    /// affine maps, the result is exact.
+    pub fn transform(&mut self, affine: &Affine) {
+        self.ops.iter_mut().for_each(|op| op.transform(affine));
+    }
+}
+
+/// The point of the cubic Bézier curve at `t`.
+fn cubic_point<F: RealNumber>(curve: &[Point2<F>; 4], t: F) -> Point2<F> {
+    let s = F::one() - t;
+    let coords = curve[0].coords * (s * s * s)
+        + curve[1].coords * (_c::<F>(3.0) * s * s * t)
+        + curve[2].coords * (_c::<F>(3.0) * s * t * t)
+        + curve[3].coords * (t * t * t);
+    Point2::from(coords)
+}
+
+/// The parameters strictly between 0 and 1 where the curve turns around in x
+/// or y, the roots of the derivative of each coordinate.
+fn cubic_extrema<F: RealNumber>(curve: &[Point2<F>; 4]) -> Vec<F> {
+    let mut roots = vec![];
+    let (a, b, c) = (
+        curve[1] - curve[0],
+        curve[2] - curve[1],
+        curve[3] - curve[2],
+    );
+    for (a, b, c) in [(a.x, b.x, c.x), (a.y, b.y, c.y)] {
+        // the derivative divided by 3 is qa t^2 + qb t + qc
+        let qa = a - _c::<F>(2.0) * b + c;
+        let qb = _c::<F>(2.0) * (b - a);
+        let qc = a;
+        if qa.abs() <= F::default_epsilon() {
+            if qb.abs() > F::default_epsilon() {
+                roots.push(-qc / qb);
+            }
+            continue;
+        }
+        let discriminant = qb * qb - _c::<F>(4.0) * qa * qc;
+        if discriminant >= F::zero() {
+            let root = discriminant.sqrt();
+            roots.push((-qb + root) / (_c::<F>(2.0) * qa));
+            roots.push((-qb - root) / (_c::<F>(2.0) * qa));
+        }
+    }
+    roots.retain(|t| *t > F::zero() && *t < F::one());
+    roots
+}
+
+impl<F: RealNumber + Display> OpSet<F> {
+    /// Flattens the ops into one polyline per subpath, approximating curves
+    /// within `tolerance`. Subpaths made of a lone move are dropped.
+    pub fn to_polylines(&self, tolerance: F) -> Vec<Vec<Point2<F>>> {
+        let mut polylines = vec![];
+        let mut current: Vec<Point2<F>> = vec![];
+        for item in self.ops.iter() {
+            match item.op {
+                OpType::Move => {
+                    if current.len() > 1 {
+                        polylines.push(std::mem::take(&mut current));
+                    }
+                    current.clear();
+                    current.push(Point2::new(item.data[0], item.data[1]));
+                }
+                OpType::LineTo => current.push(Point2::new(item.data[0], item.data[1])),
+                OpType::BCurveTo => {
+                    let start = current
+                        .last()
+                        .copied()
+                        .unwrap_or_else(|| Point2::new(item.data[0], item.data[1]));
+                    let curve = [
+                        start,
+                        Point2::new(item.data[0], item.data[1]),
+                        Point2::new(item.data[2], item.data[3]),
+                        Point2::new(item.data[4], item.data[5]),
+                    ];
+                    let points = points_on_bezier_curves(&curve, tolerance, None);
+                    if current.is_empty() {
+                        current.push(start);
+                    }
+                    current.extend(points.into_iter().skip(1));
+                }
+            }
+        }
+        if current.len() > 1 {
+            polylines.push(current);
+        }
+        polylines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::relative_eq;
+    use nalgebra::Point2;
+    use piet::kurbo::Affine;
+
+    use super::{Op, OpSet, OpSetType, OpType};
+
+    #[test]
+    fn polylines_split_at_moves_and_flatten_curves() {
+        let set = OpSet {
+            op_set_type: OpSetType::Path,
+            ops: vec![
+                Op {
+                    op: OpType::Move,
+                    data: vec![0.0, 0.0],
+                },
+                Op {
+                    op: OpType::LineTo,
+                    data: vec![10.0, 0.0],
+                },
+                Op {
+                    op: OpType::Move,
+                    data: vec![0.0, 10.0],
+                },
+                Op {
+                    op: OpType::BCurveTo,
+                    data: vec![0.0, 20.0, 20.0, 20.0, 20.0, 10.0],
+                },
+                Op {
+                    op: OpType::Move,
+                    data: vec![5.0, 5.0],
+                },
+            ],
+            size: None,
+            path: None,
+        };
+        let polylines = set.to_polylines(0.1_f64);
+        assert_eq!(polylines.len(), 2);
+        assert_eq!(polylines[0].len(), 2);
+        let curve = &polylines[1];
+        assert!(curve.len() > 4);
+        assert_eq!(curve[0].x, 0.0);
+        assert_eq!(curve[curve.len() - 1].x, 20.0);
+        assert_eq!(curve[curve.len() - 1].y, 10.0);
+    }
+
+    #[test]
+    fn transform_maps_every_point() {
+        let mut set = OpSet {
+            op_set_type: OpSetType::Path,
+            ops: vec![
+                Op {
+                    op: OpType::Move,
+                    data: vec![1.0, 2.0],
+                },
+                Op {
+                    op: OpType::BCurveTo,
+                    data: vec![3.0, 4.0, 5.0, 6.0, 7.0, 8.0],
+                },
+            ],
+            size: None,
+            path: None,
+        };
+        set.transform(&(Affine::translate((10.0, 0.0)) * Affine::scale(2.0)));
+        assert_eq!(set.ops[0].data, vec![12.0, 4.0]);
+        assert_eq!(set.ops[1].data, vec![16.0, 8.0, 20.0, 12.0, 24.0, 16.0]);
+    }
+
+    #[test]
+    fn bounds_follow_curve_extrema() {
+        let set = OpSet {
+            op_set_type: OpSetType::Path,
+            ops: vec![
+                Op {
+                    op: OpType::Move,
+                    data: vec![0.0, 0.0],
+                },
+                Op {
+                    op: OpType::BCurveTo,
+                    data: vec![0.0, 10.0, 10.0, 10.0, 10.0, 0.0],
+                },
+            ],
+            size: None,
+            path: None,
+        };
+        let bounds = set.bounds().unwrap();
+        assert_eq!(bounds.min, Point2::new(0.0, 0.0));
+        // the curve peaks at three quarters of its control polygon
+        assert!(relative_eq!(bounds.max.x, 10.0));
+        assert!(relative_eq!(bounds.max.y, 7.5));
+        assert_eq!(OpSet::<f64> { ops: vec![], ..set }.bounds(), None);
+    }
+}
@@ -0,0 +1,321 @@
+use std::borrow::BorrowMut;
+use std::collections::HashMap;
+use std::marker::PhantomData;
+
+use nalgebra::Point2;
+use nalgebra_glm::RealNumber;
+use points_on_curve::{distance_to_segment_squared, simplify};
+
+use super::gesture::fill_gap;
+use super::traits::PatternFiller;
+use crate::graphics::drawable::DrawOptions;
+use crate::graphics::drawable_ops::{Bounds, Op, OpSet, OpSetType};
+use crate::graphics::geometry::winding_number;
+use crate::graphics::paint::FillRule;
+use crate::graphics::renderer::_double_line;
+use crate::graphics::{_c, _to_f64};
+
+/// Upper bound on the number of distance samples along each side of the
+/// bounding box, which keeps large shapes with small gaps affordable.
+const MAX_SAMPLES: f64 = 400.0;
+
+/// Fills polygons with copies of their outline offset inwards by multiples of
+/// `hachure_gap`, until the shape collapses. Holes are offset outwards at the
+/// same time, so rings follow both the outer edge and the holes.
+pub struct ContourFiller<F> {
+    _phantom: PhantomData<F>,
+}
+
+impl<F, P> PatternFiller<F, P> for ContourFiller<F>
+where
+    F: RealNumber,
+    P: BorrowMut<Vec<Vec<Point2<F>>>>,
+{
+    fn fill_polygons(&self, mut polygon_list: P, o: &mut DrawOptions) -> OpSet<F> {
+        let mut ops: Vec<Op<F>> = vec![];
+        let rule = o.fill_rule.unwrap_or_default();
+        for ring in contour_rings(polygon_list.borrow_mut(), fill_gap(o), rule) {
+            for pair in ring.windows(2) {
+                ops.extend(_double_line(
+                    pair[0].x, pair[0].y, pair[1].x, pair[1].y, o, true,
+                ));
+            }
+        }
+        OpSet {
+            op_set_type: OpSetType::FillSketch,
+            ops,
+            size: None,
+            path: None,
+        }
+    }
+}
+
+impl<F: RealNumber> ContourFiller<F> {
+    pub fn new() -> Self {
+        ContourFiller {
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<F: RealNumber> Default for ContourFiller<F> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The closed rings at distance `gap`, `2 * gap` and so on inside the
+/// polygons, which are combined with `rule`. The rings are traced
+/// on a sampled distance field and each one ends where it started.
+pub(crate) fn contour_rings<F: RealNumber>(
+    polygons: &[Vec<Point2<F>>],
+    gap: F,
+    rule: FillRule,
+) -> Vec<Vec<Point2<F>>> {
+    let polygons: Vec<&Vec<Point2<F>>> = polygons.iter().filter(|p| p.len() > 2).collect();
+    let Some(Bounds { min, max }) = Bounds::of_points(polygons.iter().flat_map(|p| p.iter()))
+    else {
+        return vec![];
+    };
+    let extent = (max.x - min.x).max(max.y - min.y);
+    let cell = (gap / _c(4.0)).max(extent / _c(MAX_SAMPLES as f32));
+    if cell <= F::zero() {
+        return vec![];
+    }
+    // one cell of margin keeps every ring closed inside the grid
+    let origin = Point2::new(min.x - cell, min.y - cell);
+    let nx = (_to_f64((max.x - min.x) / cell).ceil() as usize) + 2;
+    let ny = (_to_f64((max.y - min.y) / cell).ceil() as usize) + 2;
+    let field = DistanceField::new(&polygons, rule, origin, cell, nx, ny);
+    let deepest = field
+        .values
+        .iter()
+        .fold(F::zero(), |deepest, v| deepest.max(*v));
+
+    let mut rings = vec![];
+    let mut level = gap;
+    while level < deepest {
+        for ring in field.iso_lines(level) {
+            rings.push(simplify_ring(&ring, cell / _c(2.0)));
+        }
+        level += gap;
+    }
+    rings
+}
+
+/// Signed distance to the nearest polygon edge, sampled on a grid of
+/// `(nx + 1) * (ny + 1)` points. Positive inside the polygons.
+struct DistanceField<F: RealNumber> {
+    origin: Point2<F>,
+    cell: F,
+    nx: usize,
+    values: Vec<F>,
+}
+
+impl<F: RealNumber> DistanceField<F> {
+    fn new(
+        polygons: &[&Vec<Point2<F>>],
+        rule: FillRule,
+        origin: Point2<F>,
+        cell: F,
+        nx: usize,
+        ny: usize,
+    ) -> Self {
+        let mut values = Vec::with_capacity((nx + 1) * (ny + 1));
+        for j in 0..=ny {
+            for i in 0..=nx {
+                let p = Point2::new(
+                    origin.x + cell * _c(i as f32),
+                    origin.y + cell * _c(j as f32),
+                );
+                let mut winding = 0;
+                let mut nearest_sq: Option<F> = None;
+                for polygon in polygons.iter() {
+                    winding += winding_number(polygon, &p);
+                    for (k, a) in polygon.iter().enumerate() {
+                        let b = polygon[(k + 1) % polygon.len()];
+                        let d_sq = distance_to_segment_squared(p, *a, b);
+                        nearest_sq = Some(nearest_sq.map_or(d_sq, |n| n.min(d_sq)));
+                    }
+                }
+                let d = nearest_sq.map_or_else(F::zero, |d_sq| d_sq.sqrt());
+                values.push(if rule.is_inside(winding) { d } else { -d });
+            }
+        }
+        DistanceField {
+            origin,
+            cell,
+            nx,
+            values,
+        }
+    }
+
+    fn value(&self, i: usize, j: usize) -> F {
+        self.values[j * (self.nx + 1) + i]
+    }
+
+    fn point(&self, i: usize, j: usize) -> Point2<F> {
+        Point2::new(
+            self.origin.x + self.cell * _c(i as f32),
+            self.origin.y + self.cell * _c(j as f32),
+        )
+    }
+
+    /// Traces the lines where the field equals `level` with marching squares.
+    fn iso_lines(&self, level: F) -> Vec<Vec<Point2<F>>> {
+        let ny = self.values.len() / (self.nx + 1) - 1;
+        // grid edges are keyed so neighbouring cells share the crossing point
+        let horizontal = |i: usize, j: usize| (j * (self.nx + 1) + i) * 2;
+        let vertical = |i: usize, j: usize| (j * (self.nx + 1) + i) * 2 + 1;
+        let mut crossings: HashMap<usize, Point2<F>> = HashMap::new();
+        let mut segments: Vec<(usize, usize)> = vec![];
+        for j in 0..ny {
+            for i in 0..self.nx {
+                let corners = [(i, j), (i + 1, j), (i + 1, j + 1), (i, j + 1)];
+                let inside = corners.map(|(ci, cj)| self.value(ci, cj) >= level);
+                // bottom, right, top and left edges as corner pairs
+                let edges = [
+                    (0, 1, horizontal(i, j)),
+                    (1, 2, vertical(i + 1, j)),
+                    (3, 2, horizontal(i, j + 1)),
+                    (0, 3, vertical(i, j)),
+                ];
+                let crossed: Vec<usize> = (0..4)
+                    .filter(|e| inside[edges[*e].0] != inside[edges[*e].1])
+                    .collect();
+                for e in crossed.iter() {
+                    let (a, b, key) = edges[*e];
+                    crossings.entry(key).or_insert_with(|| {
+                        let (pa, pb) = (corners[a], corners[b]);
+                        let (va, vb) = (self.value(pa.0, pa.1), self.value(pb.0, pb.1));
+                        let t = (level - va) / (vb - va);
+                        let (a, b) = (self.point(pa.0, pa.1), self.point(pb.0, pb.1));
+                        a + (b - a) * t
+                    });
+                }
+                match crossed.as_slice() {
+                    [a, b] => segments.push((edges[*a].2, edges[*b].2)),
+                    [_, _, _, _] => {
+                        // a saddle, decided by the value at the centre of the cell
+                        let centre = corners
+                            .iter()
+                            .fold(F::zero(), |sum, (ci, cj)| sum + self.value(*ci, *cj))
+                            / _c(4.0);
+                        let key = |e: usize| edges[e].2;
+                        if (centre >= level) == inside[0] {
+                            segments.push((key(0), key(1)));
+                            segments.push((key(2), key(3)));
+                        } else {
+                            segments.push((key(3), key(0)));
+                            segments.push((key(1), key(2)));
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+        link_segments(&segments)
+            .into_iter()
+            .map(|keys| keys.iter().map(|k| crossings[k]).collect())
+            .collect()
+    }
+}
+
+/// Joins segments that share an end into chains of keys.
+fn link_segments(segments: &[(usize, usize)]) -> Vec<Vec<usize>> {
+    let mut ends: HashMap<usize, Vec<usize>> = HashMap::new();
+    for (index, (a, b)) in segments.iter().enumerate() {
+        ends.entry(*a).or_default().push(index);
+        ends.entry(*b).or_default().push(index);
+    }
+    let mut used = vec![false; segments.len()];
+    let mut chains = vec![];
+    for start in 0..segments.len() {
+        if used[start] {
+            continue;
+        }
+        used[start] = true;
+        let mut chain = vec![segments[start].0, segments[start].1];
+        for _ in 0..2 {
+            loop {
+                let last = chain[chain.len() - 1];
+                let next = ends[&last].iter().copied().find(|s| !used[*s]);
+                let Some(next) = next else {
+                    break;
+                };
+                used[next] = true;
+                let (a, b) = segments[next];
+                chain.push(if a == last { b } else { a });
+            }
+            chain.reverse();
+        }
+        chains.push(chain);
+    }
+    chains
+}
+
+/// Drops points that lie within `tolerance` of the ring through their
+/// neighbours. The closed ring is split at its farthest point from the start
+/// and each half is simplified on its own.
+fn simplify_ring<F: RealNumber>(points: &[Point2<F>], tolerance: F) -> Vec<Point2<F>> {
+    if points.len() < 3 {
+        return points.to_vec();
+    }
+    let far = (1..points.len() - 1)
+        .max_by(|a, b| {
+            let da = nalgebra::distance(&points[0], &points[*a]);
+            let db = nalgebra::distance(&points[0], &points[*b]);
+            da.partial_cmp(&db).unwrap()
+        })
+        .unwrap();
+    let mut ring = simplify(&points[..=far], tolerance);
+    ring.extend(simplify(&points[far..], tolerance).into_iter().skip(1));
+    ring
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::Point2;
+
+    use super::contour_rings;
+    use crate::graphics::paint::FillRule;
+
+    fn square(min: f64, max: f64) -> Vec<Point2<f64>> {
+        vec![
+            Point2::new(min, min),
+            Point2::new(max, min),
+            Point2::new(max, max),
+            Point2::new(min, max),
+        ]
+    }
+
+    #[test]
+    fn rings_shrink_until_the_shape_collapses() {
+        let rings = contour_rings(&[square(0.0, 100.0)], 10.0, FillRule::EvenOdd);
+        // offsets at 10, 20, 30 and 40 fit in a square of half width 50
+        assert_eq!(rings.len(), 4);
+        for (ring, level) in rings.iter().zip([10.0, 20.0, 30.0, 40.0]) {
+            assert_eq!(ring.first(), ring.last());
+            for p in ring.iter() {
+                let depth = p.x.min(p.y).min(100.0 - p.x).min(100.0 - p.y);
+                assert!((depth - level).abs() < 1.0, "{} at depth {}", p, depth);
+            }
+        }
+    }
+
+    #[test]
+    fn holes_get_their_own_rings() {
+        let rings = contour_rings(
+            &[square(0.0, 100.0), square(40.0, 60.0)],
+            8.0,
+            FillRule::EvenOdd,
+        );
+        // the band around the hole is 40 wide, so the offsets at 8 and 16
+        // each give a ring inside the outer edge and one around the hole
+        assert_eq!(rings.len(), 4);
+        assert!(rings
+            .iter()
+            .flatten()
+            .all(|p| !(p.x > 40.0 && p.x < 60.0 && p.y > 40.0 && p.y < 60.0)));
+    }
+}
@@ -0,0 +1,138 @@
+//! Helpers shared by the fillers that draw a region in one continuous stroke.
+
+use nalgebra::Point2;
+use nalgebra_glm::RealNumber;
+use points_on_curve::distance_to_segment_squared;
+
+use crate::graphics::drawable::DrawOptions;
+use crate::graphics::drawable_ops::Op;
+use crate::graphics::paint::FillRule;
+use crate::graphics::renderer::_curve_with_offset;
+use crate::graphics::{_c, _to_f64};
+
+/// The spacing between neighbouring fill strokes, from `hachure_gap` or four
+/// times the stroke width when it is negative.
+pub(crate) fn fill_gap<F: RealNumber>(o: &DrawOptions) -> F {
+    let mut gap = o.hachure_gap.map(_c::<F>).unwrap_or_else(|| _c::<F>(-1.0));
+    if gap < F::zero() {
+        gap = o.stroke_width.map(_c::<F>).unwrap_or_else(|| _c::<F>(1.0)) * _c::<F>(4.0);
+    }
+    gap.max(_c::<F>(0.1))
+}
+
+/// The area covered by a list of polygons under the `fill_rule` of the
+/// options.
+pub(crate) struct Region<'a, F: RealNumber> {
+    polygons: &'a [Vec<Point2<F>>],
+    rule: FillRule,
+}
+
+impl<'a, F: RealNumber> Region<'a, F> {
+    pub(crate) fn new(polygons: &'a [Vec<Point2<F>>], o: &DrawOptions) -> Self {
+        Region {
+            polygons,
+            rule: o.fill_rule.unwrap_or_default(),
+        }
+    }
+
+    pub(crate) fn contains(&self, p: &Point2<F>) -> bool {
+        self.rule.contains(self.polygons, p)
+    }
+
+    /// Distance from `p` to the nearest polygon edge.
+    fn distance(&self, p: &Point2<F>) -> Option<F> {
+        let mut nearest_sq: Option<F> = None;
+        for polygon in self.polygons.iter().filter(|p| p.len() > 1) {
+            for (i, a) in polygon.iter().enumerate() {
+                let b = &polygon[(i + 1) % polygon.len()];
+                let d_sq = distance_to_segment_squared(p, a, b);
+                nearest_sq = Some(nearest_sq.map_or(d_sq, |n| n.min(d_sq)));
+            }
+        }
+        nearest_sq.map(|d_sq| d_sq.sqrt())
+    }
+
+    /// Whether the pen can go straight from `a` to `b` without leaving the
+    /// region by more than `tolerance`, such as along an edge.
+    pub(crate) fn connects(&self, a: &Point2<F>, b: &Point2<F>, tolerance: F) -> bool {
+        let steps = (_to_f64(nalgebra::distance(a, b) / tolerance).ceil() as usize).clamp(1, 256);
+        (0..=steps).all(|i| {
+            let p = a + (b - a) * (_c::<F>(i as f32) / _c::<F>(steps as f32));
+            self.contains(&p) || self.distance(&p).is_some_and(|d| d <= tolerance)
+        })
+    }
+
+    /// Splits `path` into the runs that lie inside the region, ending each run
+    /// where the path crosses the boundary.
+    pub(crate) fn clip(&self, path: &[Point2<F>]) -> Vec<Vec<Point2<F>>> {
+        let mut runs = vec![];
+        let mut current: Vec<Point2<F>> = vec![];
+        let mut previous: Option<(Point2<F>, bool)> = None;
+        for p in path.iter() {
+            let inside = self.contains(p);
+            if let Some((q, was_inside)) = previous {
+                if inside != was_inside {
+                    let crossing = self.crossing(&q, p, was_inside);
+                    current.push(crossing);
+                    if was_inside {
+                        runs.push(std::mem::take(&mut current));
+                    }
+                }
+            }
+            if inside {
+                current.push(*p);
+            }
+            previous = Some((*p, inside));
+        }
+        runs.push(current);
+        runs.retain(|run| run.len() > 1);
+        runs
+    }
+
+    /// The point between `inside` and `outside` where the boundary is crossed,
+    /// found by bisection.
+    fn crossing(&self, a: &Point2<F>, b: &Point2<F>, a_inside: bool) -> Point2<F> {
+        let (mut inside, mut outside) = if a_inside { (*a, *b) } else { (*b, *a) };
+        for _ in 0..16 {
+            let middle = inside + (outside - inside) / _c::<F>(2.0);
+            if self.contains(&middle) {
+                inside = middle;
+            } else {
+                outside = middle;
+            }
+        }
+        inside
+    }
+}
+
+/// Joins consecutive runs into one stroke wherever the pen can travel between
+/// them inside the region, so it is only lifted where it has to be.
+pub(crate) fn join_runs<F: RealNumber>(
+    runs: Vec<Vec<Point2<F>>>,
+    region: &Region<F>,
+    tolerance: F,
+) -> Vec<Vec<Point2<F>>> {
+    let mut strokes: Vec<Vec<Point2<F>>> = vec![];
+    for run in runs {
+        match strokes.last_mut() {
+            Some(stroke) if region.connects(&stroke[stroke.len() - 1], &run[0], tolerance) => {
+                stroke.extend(run)
+            }
+            _ => strokes.push(run),
+        }
+    }
+    strokes
+}
+
+/// Draws each stroke as one rough curve through its points.
+pub(crate) fn render_strokes<F: RealNumber>(
+    strokes: &[Vec<Point2<F>>],
+    o: &mut DrawOptions,
+) -> Vec<Op<F>> {
+    let offset = _c::<F>(1.0 + o.roughness.unwrap_or(0.0) * 0.2);
+    strokes
+        .iter()
+        .filter(|stroke| stroke.len() > 1)
+        .flat_map(|stroke| _curve_with_offset(stroke, offset, o))
+        .collect()
+}
@@ -0,0 +1,192 @@
+use std::borrow::BorrowMut;
+
+use nalgebra::{Point2, Vector2};
+use nalgebra_glm::RealNumber;
+
+use super::gesture::fill_gap;
+use super::scan_line_hachure::polygon_hachure_lines;
+use super::traits::PatternFiller;
+use crate::graphics::drawable::DrawOptions;
+use crate::graphics::drawable_ops::{Bounds, OpSet, OpSetType};
+use crate::graphics::geometry::Line;
+use crate::graphics::raster::GrayImage;
+use crate::graphics::renderer::_double_line;
+use crate::graphics::{_cc, _to_f64};
+
+/// The hachure layers of a halftone fill, as the angle added to
+/// `hachure_angle` and the darkness from which the layer is drawn. Like
+/// `HatchFiller`, darker tones cross the first layer with more directions.
+const LAYERS: [(f32, f64); 4] = [(0.0, 0.15), (90.0, 0.4), (45.0, 0.65), (-45.0, 0.85)];
+
+/// Hachures polygons with an image, drawing denser cross-hatching where the
+/// image is darker. The image is stretched over the bounding box of the filled
+/// polygons, or over a fixed rectangle set with `with_placement`.
+pub struct HalftoneFiller<F: RealNumber> {
+    image: GrayImage,
+    placement: Option<(Point2<F>, Vector2<F>)>,
+}
+
+impl<F, P> PatternFiller<F, P> for HalftoneFiller<F>
+where
+    F: RealNumber,
+    P: BorrowMut<Vec<Vec<Point2<F>>>>,
+{
+    fn fill_polygons(&self, mut polygon_list: P, o: &mut DrawOptions) -> OpSet<F> {
+        let mut ops = vec![];
+        for line in self.halftone_lines(polygon_list.borrow_mut(), o) {
+            ops.extend(_double_line(
+                line.start_point.x,
+                line.start_point.y,
+                line.end_point.x,
+                line.end_point.y,
+                o,
+                true,
+            ));
+        }
+        OpSet {
+            op_set_type: OpSetType::FillSketch,
+            ops,
+            size: None,
+            path: None,
+        }
+    }
+}
+
+impl<F: RealNumber> HalftoneFiller<F> {
+    pub fn new(image: GrayImage) -> Self {
+        HalftoneFiller {
+            image,
+            placement: None,
+        }
+    }
+
+    /// Places the image in the rectangle at `(x, y)`. Parts of the polygons
+    /// outside the rectangle are left blank.
+    pub fn with_placement(mut self, x: F, y: F, width: F, height: F) -> Self {
+        self.placement = Some((Point2::new(x, y), Vector2::new(width, height)));
+        self
+    }
+
+    /// The hachure segments of every layer, cut to where the image is dark
+    /// enough for that layer.
+    fn halftone_lines(&self, polygons: &mut Vec<Vec<Point2<F>>>, o: &DrawOptions) -> Vec<Line<F>> {
+        let Some((origin, size)) = self.placement.or_else(|| bounding_box(polygons)) else {
+            return vec![];
+        };
+        let darkness = |p: Point2<F>| {
+            let u = _to_f64((p.x - origin.x) / size.x);
+            let v = _to_f64((p.y - origin.y) / size.y);
+            if (0.0..=1.0).contains(&u) && (0.0..=1.0).contains(&v) {
+                1.0 - self.image.luminance(u, v)
+            } else {
+                0.0
+            }
+        };
+        let step = _to_f64(fill_gap::<F>(o)) / 2.0;
+        let mut segments = vec![];
+        for (angle, threshold) in LAYERS {
+            let mut layer = o.clone();
+            layer.set_hachure_angle(Some(o.hachure_angle.unwrap_or(0.0) + angle));
+            for line in polygon_hachure_lines(polygons, &layer) {
+                segments.extend(dark_runs(&line, step, |p| darkness(p) >= threshold));
+            }
+        }
+        segments
+    }
+}
+
+fn bounding_box<F: RealNumber>(polygons: &[Vec<Point2<F>>]) -> Option<(Point2<F>, Vector2<F>)> {
+    let bounds = Bounds::of_points(polygons.iter().flatten())?;
+    (bounds.width() > F::zero() && bounds.height() > F::zero())
+        .then(|| (bounds.min, bounds.max - bounds.min))
+}
+
+/// Splits `line` into pieces about `step` long and joins the consecutive
+/// pieces whose midpoint is `dark`.
+fn dark_runs<F: RealNumber>(
+    line: &Line<F>,
+    step: f64,
+    dark: impl Fn(Point2<F>) -> bool,
+) -> Vec<Line<F>> {
+    let pieces = (_to_f64(line.length()) / step).ceil().max(1.0) as usize;
+    let at = |t: f64| {
+        line.start_point + (line.end_point - line.start_point) * _cc::<F>(t / pieces as f64)
+    };
+    let mut runs = vec![];
+    let mut start = None;
+    for i in 0..pieces {
+        match (dark(at(i as f64 + 0.5)), start) {
+            (true, None) => start = Some(i),
+            (false, Some(first)) => {
+                runs.push(Line::from(&[at(first as f64), at(i as f64)]));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(first) = start {
+        runs.push(Line::from(&[at(first as f64), line.end_point]));
+    }
+    runs
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::Point2;
+
+    use super::HalftoneFiller;
+    use crate::graphics::drawable::DrawOptionsBuilder;
+    use crate::graphics::raster::GrayImage;
+
+    fn square() -> Vec<Vec<Point2<f64>>> {
+        vec![vec![
+            Point2::new(0.0, 0.0),
+            Point2::new(100.0, 0.0),
+            Point2::new(100.0, 100.0),
+            Point2::new(0.0, 100.0),
+        ]]
+    }
+
+    #[test]
+    fn darker_tones_get_more_hachure() {
+        let o = DrawOptionsBuilder::default()
+            .hachure_gap(5.0)
+            .hachure_angle(0.0)
+            .build()
+            .unwrap();
+        let length = |filler: HalftoneFiller<f64>| -> f64 {
+            let lines = filler.halftone_lines(&mut square(), &o);
+            lines.iter().map(|l| l.length()).sum()
+        };
+        let tone = |luminance: u8| GrayImage::from_luma8(1, 1, &[luminance]).unwrap();
+        let white = length(HalftoneFiller::new(tone(255)));
+        let light = length(HalftoneFiller::new(tone(190)));
+        let black = length(HalftoneFiller::new(tone(0)));
+        assert_eq!(white, 0.0);
+        assert!(light > 1500.0);
+        // all four layers are drawn
+        assert!(black > light * 3.5);
+    }
+
+    #[test]
+    fn lines_follow_the_image() {
+        let o = DrawOptionsBuilder::default()
+            .hachure_gap(4.0)
+            .build()
+            .unwrap();
+        let pixels: Vec<u8> = (0..20).map(|i| if i < 10 { 0 } else { 255 }).collect();
+        let left_dark = GrayImage::from_luma8(20, 1, &pixels).unwrap();
+        let lines = HalftoneFiller::new(left_dark).halftone_lines(&mut square(), &o);
+        assert!(!lines.is_empty());
+        for line in lines {
+            assert!(line.start_point.x <= 55.0 && line.end_point.x <= 55.0);
+        }
+
+        let placed = HalftoneFiller::new(GrayImage::from_luma8(1, 1, &[0]).unwrap())
+            .with_placement(0.0, 0.0, 30.0, 30.0);
+        for line in placed.halftone_lines(&mut square(), &o) {
+            assert!(line.start_point.x <= 32.0 && line.start_point.y <= 32.0);
+            assert!(line.end_point.x <= 32.0 && line.end_point.y <= 32.0);
+        }
+    }
+}
@@ -3,18 +3,30 @@ use std::borrow::BorrowMut;
 use nalgebra::{Point2, Scalar};
 use nalgebra_glm::RealNumber;
 
+use self::contour_filler::ContourFiller;
 use self::dashed_filler::DashedFiller;
 use self::dot_filler::DotFiller;
 use self::hatch_filler::HatchFiller;
+pub use self::scan_line_hachure::polygon_hachure_lines;
 use self::scan_line_hachure::ScanlineHachureFiller;
-use self::traits::PatternFiller;
+use self::scribble_filler::ScribbleFiller;
+use self::spiral_filler::SpiralFiller;
+use self::stipple_filler::StippleFiller;
+pub use self::traits::PatternFiller;
 use self::zig_zag_filler::ZigZagFiller;
 use self::zig_zag_line_filler::ZigZagLineFiller;
+pub use crate::graphics::geometry::{rotate_points, Line};
 
+pub mod contour_filler;
 pub mod dashed_filler;
 pub mod dot_filler;
+mod gesture;
+pub mod halftone_filler;
 pub mod hatch_filler;
 pub mod scan_line_hachure;
+pub mod scribble_filler;
+pub mod spiral_filler;
+pub mod stipple_filler;
 pub mod traits;
 pub mod zig_zag_filler;
 pub mod zig_zag_line_filler;
@@ -26,6 +38,10 @@ pub enum FillerType {
     HatchFiller,
     ZigZagFiller,
     ZigZagLineFiller,
+    ContourFiller,
+    SpiralFiller,
+    ScribbleFiller,
+    StippleFiller,
 }
 
 pub fn get_filler<'a, F, P>(f: FillerType) -> Box<dyn PatternFiller<F, P> + 'a>
@@ -40,5 +56,9 @@ where
         FillerType::HatchFiller => Box::new(HatchFiller::new()),
         FillerType::ZigZagFiller => Box::new(ZigZagFiller::new()),
         FillerType::ZigZagLineFiller => Box::new(ZigZagLineFiller::new()),
+        FillerType::ContourFiller => Box::new(ContourFiller::new()),
+        FillerType::SpiralFiller => Box::new(SpiralFiller::new()),
+        FillerType::ScribbleFiller => Box::new(ScribbleFiller::new()),
+        FillerType::StippleFiller => Box::new(StippleFiller::new()),
     }
 }
@@ -2,13 +2,14 @@ use std::borrow::BorrowMut;
 use std::cmp::Ordering;
 use std::marker::PhantomData;
 
-use nalgebra::{Point2, Scalar};
+use nalgebra::Point2;
 use nalgebra_glm::RealNumber;
 
 use super::traits::PatternFiller;
 use crate::graphics::drawable::DrawOptions;
 use crate::graphics::drawable_ops::OpSet;
 use crate::graphics::geometry::{rotate_lines, rotate_points, Line};
+use crate::graphics::paint::FillRule;
 use crate::graphics::{_c, _to_f64};
 
 #[derive(Clone)]
@@ -17,6 +18,7 @@ struct EdgeEntry<F: RealNumber> {
     pub(crate) ymax: F,
     pub(crate) x: F,
     pub(crate) islope: F,
+    pub(crate) winding: i32,
 }
 
 impl<F: RealNumber> std::fmt::Display for EdgeEntry<F> {
@@ -36,6 +38,9 @@ struct ActiveEdgeEntry<F: RealNumber> {
     pub(crate) edge: EdgeEntry<F>,
 }
 
+/// The hachure lines covering `polygon_list` at the `hachure_angle` and
+/// `hachure_gap` of `options`, without any roughness applied. Overlapping
+/// contours are resolved with the `fill_rule` of `options`.
 pub fn polygon_hachure_lines<F: RealNumber>(
     polygon_list: &mut Vec<Vec<Point2<F>>>,
     options: &DrawOptions,
@@ -55,7 +60,8 @@ pub fn polygon_hachure_lines<F: RealNumber>(
             .for_each(|polygon| *polygon = rotate_points(polygon, &center, _c(angle)))
     }
 
-    let mut lines = straight_hachure_lines(polygon_list, _c(gap));
+    let rule = options.fill_rule.unwrap_or_default();
+    let mut lines = straight_hachure_lines(polygon_list, _c(gap), rule);
 
     if angle != 0.0 {
         polygon_list
@@ -67,10 +73,11 @@ pub fn polygon_hachure_lines<F: RealNumber>(
     return lines;
 }
 
-fn straight_hachure_lines<F: Scalar>(polygon_list: &mut [Vec<Point2<F>>], gap: F) -> Vec<Line<F>>
-where
-    F: RealNumber,
-{
+fn straight_hachure_lines<F: RealNumber>(
+    polygon_list: &mut [Vec<Point2<F>>],
+    gap: F,
+    rule: FillRule,
+) -> Vec<Line<F>> {
     // Reference: https://github.com/gicentre/handy/blob/main/Handy/src/org/gicentre/handy/HandyRenderer.java
     // Sketchy Rendering for Information Visualization
     let mut vertex_array: Vec<Vec<Point2<F>>> = vec![];
@@ -107,6 +114,7 @@ where
                         ymax: F::max(p1.y, p2.y),
                         x: if ymin == p1.y { p1.x } else { p2.x },
                         islope: (p2.x - p1.x) / (p2.y - p1.y),
+                        winding: if p2.y > p1.y { 1 } else { -1 },
                     })
                 } else {
                     None
@@ -184,15 +192,24 @@ where
                 }
             }
         });
-        if active_edges.len() > 1 {
-            active_edges[..].chunks(2).for_each(|ae| {
-                let ce = &ae[0];
-                let ne = &ae[1];
-                lines.push(Line::from(&[
-                    Point2::new(ce.edge.x, y),
-                    Point2::new(ne.edge.x, y),
-                ]));
-            });
+        // walk the crossings from left to right, keeping the spans the rule
+        // counts as inside
+        let mut winding = 0;
+        let mut span_start = None;
+        for ae in active_edges.iter() {
+            let was_inside = rule.is_inside(winding);
+            winding += ae.edge.winding;
+            match (was_inside, rule.is_inside(winding), span_start) {
+                (false, true, _) => span_start = Some(ae.edge.x),
+                (true, false, Some(start)) => {
+                    lines.push(Line::from(&[
+                        Point2::new(start, y),
+                        Point2::new(ae.edge.x, y),
+                    ]));
+                    span_start = None;
+                }
+                _ => {}
+            }
         }
 
         y = y + gap;
@@ -264,6 +281,7 @@ mod test {
     use nalgebra::Point2;
 
     use crate::graphics::geometry::Line;
+    use crate::graphics::paint::FillRule;
 
     #[test]
     fn straight_hachure_lines() {
@@ -312,7 +330,27 @@ mod test {
                 Point2::new(1.0, 0.9000000134110451),
             ]),
         ];
-        let result = super::straight_hachure_lines(&mut input, 0.1);
+        let result = super::straight_hachure_lines(&mut input, 0.1, FillRule::EvenOdd);
         assert_eq!(expected, result);
     }
+
+    #[test]
+    fn fill_rule_decides_nested_contours() {
+        let square = |a: f64, b: f64| {
+            vec![
+                Point2::new(a, a),
+                Point2::new(b, a),
+                Point2::new(b, b),
+                Point2::new(a, b),
+            ]
+        };
+        let length = |rule: FillRule| -> f64 {
+            let mut input = vec![square(0.0, 10.0), square(3.0, 7.0)];
+            let lines = super::straight_hachure_lines(&mut input, 1.0, rule);
+            lines.iter().map(|l| l.length()).sum()
+        };
+        // both contours run the same way, so only even-odd leaves a hole
+        assert_eq!(length(FillRule::NonZero), 100.0);
+        assert_eq!(length(FillRule::EvenOdd), 84.0);
+    }
 }
@@ -0,0 +1,124 @@
+use std::borrow::BorrowMut;
+use std::marker::PhantomData;
+
+use nalgebra::Point2;
+use nalgebra_glm::RealNumber;
+
+use super::gesture::{fill_gap, render_strokes, Region};
+use super::scan_line_hachure::polygon_hachure_lines;
+use super::traits::PatternFiller;
+use crate::graphics::drawable::DrawOptions;
+use crate::graphics::drawable_ops::{OpSet, OpSetType};
+use crate::graphics::{_c, _cc};
+
+/// Fills polygons with hachure lines drawn back and forth in one stroke, turning
+/// at the edges. Each turn overshoots the edge by a random amount of up to
+/// half of `hachure_gap`.
+pub struct ScribbleFiller<F> {
+    _phantom: PhantomData<F>,
+}
+
+impl<F, P> PatternFiller<F, P> for ScribbleFiller<F>
+where
+    F: RealNumber,
+    P: BorrowMut<Vec<Vec<Point2<F>>>>,
+{
+    fn fill_polygons(&self, mut polygon_list: P, o: &mut DrawOptions) -> OpSet<F> {
+        let gap = fill_gap::<F>(o);
+        let lines = polygon_hachure_lines(polygon_list.borrow_mut(), o);
+        let region = Region::new(polygon_list.borrow(), o);
+        let mut strokes: Vec<Vec<Point2<F>>> = vec![];
+        for line in lines.iter().filter(|l| l.length() > F::zero()) {
+            // continue the latest stroke that can turn into this line without
+            // leaving the shape, starting from the nearer end
+            let joined = strokes.iter().rposition(|s| {
+                let end = s[s.len() - 1];
+                let near = if nalgebra::distance(&end, &line.start_point)
+                    <= nalgebra::distance(&end, &line.end_point)
+                {
+                    line.start_point
+                } else {
+                    line.end_point
+                };
+                region.connects(&end, &near, gap)
+            });
+            let (start, end) = match joined {
+                Some(i)
+                    if nalgebra::distance(&strokes[i][strokes[i].len() - 1], &line.end_point)
+                        < nalgebra::distance(
+                            &strokes[i][strokes[i].len() - 1],
+                            &line.start_point,
+                        ) =>
+                {
+                    (line.end_point, line.start_point)
+                }
+                _ => (line.start_point, line.end_point),
+            };
+            let direction = (end - start) / line.length();
+            let mut overshoot = || gap * _c(0.5) * _cc::<F>(o.random());
+            let run = [
+                start - direction * overshoot(),
+                end + direction * overshoot(),
+            ];
+            match joined {
+                Some(i) => strokes[i].extend(run),
+                None => strokes.push(run.to_vec()),
+            }
+        }
+        OpSet {
+            op_set_type: OpSetType::FillSketch,
+            ops: render_strokes(&strokes, o),
+            size: None,
+            path: None,
+        }
+    }
+}
+
+impl<F: RealNumber> ScribbleFiller<F> {
+    pub fn new() -> Self {
+        ScribbleFiller {
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<F: RealNumber> Default for ScribbleFiller<F> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::Point2;
+
+    use super::ScribbleFiller;
+    use crate::graphics::drawable::DrawOptionsBuilder;
+    use crate::graphics::drawable_ops::{OpSetType, OpType};
+    use crate::graphics::filler::traits::PatternFiller;
+
+    #[test]
+    fn scribble_draws_one_stroke_per_part() {
+        let mut o = DrawOptionsBuilder::default()
+            .hachure_gap(5.0)
+            .hachure_angle(0.0)
+            .build()
+            .unwrap();
+        let rectangle = |x: f64| {
+            vec![
+                Point2::new(x, 0.0),
+                Point2::new(x + 40.0, 0.0),
+                Point2::new(x + 40.0, 100.0),
+                Point2::new(x, 100.0),
+            ]
+        };
+        let moves = |polygons: Vec<Vec<Point2<f64>>>, o: &mut _| {
+            let set = ScribbleFiller::new().fill_polygons(polygons, o);
+            assert_eq!(set.op_set_type, OpSetType::FillSketch);
+            set.ops.iter().filter(|op| op.op == OpType::Move).count()
+        };
+        assert_eq!(moves(vec![rectangle(0.0)], &mut o), 1);
+        // the scan lines alternate between the rectangles, each gets a stroke
+        assert_eq!(moves(vec![rectangle(0.0), rectangle(100.0)], &mut o), 2);
+    }
+}
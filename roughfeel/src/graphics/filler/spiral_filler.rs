@@ -0,0 +1,103 @@
+use std::borrow::BorrowMut;
+use std::marker::PhantomData;
+
+use nalgebra::{Point2, Vector2};
+use nalgebra_glm::RealNumber;
+
+use super::gesture::{fill_gap, join_runs, render_strokes, Region};
+use super::traits::PatternFiller;
+use crate::graphics::drawable::DrawOptions;
+use crate::graphics::drawable_ops::{Bounds, OpSet, OpSetType};
+use crate::graphics::{_c, get_pi};
+
+/// Fills polygons with an Archimedean spiral from the centre of their bounding
+/// box, with turns `hachure_gap` apart. The pen is only lifted where the
+/// clipped spiral can not be joined without leaving the shape.
+pub struct SpiralFiller<F> {
+    _phantom: PhantomData<F>,
+}
+
+impl<F, P> PatternFiller<F, P> for SpiralFiller<F>
+where
+    F: RealNumber,
+    P: BorrowMut<Vec<Vec<Point2<F>>>>,
+{
+    fn fill_polygons(&self, mut polygon_list: P, o: &mut DrawOptions) -> OpSet<F> {
+        let gap = fill_gap::<F>(o);
+        let polygons = polygon_list.borrow_mut();
+        let region = Region::new(polygons, o);
+        let runs = region.clip(&spiral_points(polygons, gap));
+        let strokes = join_runs(runs, &region, gap / _c(2.0));
+        OpSet {
+            op_set_type: OpSetType::FillSketch,
+            ops: render_strokes(&strokes, o),
+            size: None,
+            path: None,
+        }
+    }
+}
+
+impl<F: RealNumber> SpiralFiller<F> {
+    pub fn new() -> Self {
+        SpiralFiller {
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<F: RealNumber> Default for SpiralFiller<F> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Points on a spiral around the centre of the polygons' bounding box that
+/// grows by `gap` per turn until it covers every vertex.
+fn spiral_points<F: RealNumber>(polygons: &[Vec<Point2<F>>], gap: F) -> Vec<Point2<F>> {
+    let Some(Bounds { min, max }) = Bounds::of_points(polygons.iter().flatten()) else {
+        return vec![];
+    };
+    let centre = nalgebra::center(&min, &max);
+    let reach = nalgebra::distance(&centre, &max) + gap;
+    let two_pi = get_pi::<F>() * _c(2.0);
+    let mut points = vec![];
+    let mut angle = F::zero();
+    loop {
+        let radius = gap * angle / two_pi;
+        points.push(centre + Vector2::new(angle.cos(), angle.sin()) * radius);
+        if radius > reach {
+            break;
+        }
+        // steps of about two gaps along the curve, finer near the centre
+        angle += (gap * _c(2.0) / radius.max(gap)).min(get_pi::<F>() / _c(8.0));
+    }
+    points
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::Point2;
+
+    use super::SpiralFiller;
+    use crate::graphics::drawable::DrawOptionsBuilder;
+    use crate::graphics::drawable_ops::{OpSetType, OpType};
+    use crate::graphics::filler::traits::PatternFiller;
+
+    #[test]
+    fn spiral_fills_a_square_in_one_stroke() {
+        let square = vec![vec![
+            Point2::new(0.0, 0.0),
+            Point2::new(100.0, 0.0),
+            Point2::new(100.0, 100.0),
+            Point2::new(0.0, 100.0),
+        ]];
+        let mut o = DrawOptionsBuilder::default()
+            .hachure_gap(8.0)
+            .build()
+            .unwrap();
+        let set = SpiralFiller::new().fill_polygons(square, &mut o);
+        assert_eq!(set.op_set_type, OpSetType::FillSketch);
+        assert_eq!(set.ops.iter().filter(|op| op.op == OpType::Move).count(), 1);
+        assert!(set.ops.len() > 100);
+    }
+}
@@ -0,0 +1,256 @@
+use std::borrow::BorrowMut;
+
+use nalgebra::{Point2, Vector2};
+use nalgebra_glm::RealNumber;
+
+use super::gesture::{fill_gap, Region};
+use super::traits::PatternFiller;
+use crate::graphics::drawable::DrawOptions;
+use crate::graphics::drawable_ops::{Bounds, OpSet, OpSetType};
+use crate::graphics::renderer::ellipse;
+use crate::graphics::{_c, _cc, _to_f64, get_pi};
+
+/// Candidates tried around each sample before it is retired, as in Bridson's
+/// algorithm.
+const CANDIDATES: usize = 30;
+
+/// Where the dots of a stipple fill are dense.
+pub enum Shading<F: RealNumber> {
+    /// The same density everywhere.
+    Uniform,
+    /// No dots at the side of the shape opposite to the vector, rising
+    /// linearly to the full density at the side it points to.
+    Gradient(Vector2<F>),
+    /// The density at each point, from 0 to 1.
+    Function(Box<dyn Fn(Point2<F>) -> F + Send + Sync>),
+}
+
+/// Scatters dots inside polygons by Poisson-disk sampling, so no two dots are
+/// closer than the minimum distance. Samples are then kept with a probability
+/// of `density` times the shading at their position. Each dot is a sketched
+/// ellipse of `fill_weight` diameter.
+pub struct StippleFiller<F: RealNumber> {
+    min_distance: Option<F>,
+    density: F,
+    shading: Shading<F>,
+}
+
+impl<F, P> PatternFiller<F, P> for StippleFiller<F>
+where
+    F: RealNumber,
+    P: BorrowMut<Vec<Vec<Point2<F>>>>,
+{
+    fn fill_polygons(&self, mut polygon_list: P, o: &mut DrawOptions) -> OpSet<F> {
+        let mut fweight = o.fill_weight.map(_c::<F>).unwrap_or_else(|| _c::<F>(-1.0));
+        if fweight < F::zero() {
+            fweight = o.stroke_width.map(_c::<F>).unwrap_or_else(|| _c::<F>(1.0)) / _c::<F>(2.0);
+        }
+        let mut ops = vec![];
+        for p in self.stipple_points(polygon_list.borrow_mut(), o) {
+            ops.extend(ellipse(p.x, p.y, fweight, fweight, o).ops);
+        }
+        OpSet {
+            op_set_type: OpSetType::FillSketch,
+            ops,
+            size: None,
+            path: None,
+        }
+    }
+}
+
+impl<F: RealNumber> StippleFiller<F> {
+    /// A uniform stipple with dots at least `hachure_gap` apart.
+    pub fn new() -> Self {
+        StippleFiller {
+            min_distance: None,
+            density: F::one(),
+            shading: Shading::Uniform,
+        }
+    }
+
+    /// Sets the smallest distance between dots, instead of `hachure_gap`.
+    pub fn with_min_distance(mut self, min_distance: F) -> Self {
+        self.min_distance = Some(min_distance);
+        self
+    }
+
+    /// Sets the fraction of the sampled dots that are drawn where the shading
+    /// is full, from 0 to 1.
+    pub fn with_density(mut self, density: F) -> Self {
+        self.density = density;
+        self
+    }
+
+    /// Sets where the dots are dense.
+    pub fn with_shading(mut self, shading: Shading<F>) -> Self {
+        self.shading = shading;
+        self
+    }
+
+    /// Shades with `density`, which maps a point to a density from 0 to 1.
+    pub fn with_density_fn(self, density: impl Fn(Point2<F>) -> F + Send + Sync + 'static) -> Self {
+        self.with_shading(Shading::Function(Box::new(density)))
+    }
+
+    fn stipple_points(&self, polygons: &[Vec<Point2<F>>], o: &mut DrawOptions) -> Vec<Point2<F>> {
+        let Some(Bounds { min, max }) = Bounds::of_points(polygons.iter().flatten()) else {
+            return vec![];
+        };
+        let extent = (max.x - min.x).max(max.y - min.y);
+        // keeps the sample count bounded for tiny distances on large shapes
+        let radius = self
+            .min_distance
+            .unwrap_or_else(|| fill_gap(o))
+            .max(extent / _c(1000.0));
+        if radius <= F::zero() {
+            return vec![];
+        }
+        let region = Region::new(polygons, o);
+        let shade = |p: &Point2<F>| match &self.shading {
+            Shading::Uniform => F::one(),
+            Shading::Gradient(direction) => {
+                let corners = [
+                    min,
+                    Point2::new(max.x, min.y),
+                    max,
+                    Point2::new(min.x, max.y),
+                ];
+                let along = |q: &Point2<F>| q.coords.dot(direction);
+                let low = corners.iter().map(along).fold(along(&min), |a, b| a.min(b));
+                let high = corners.iter().map(along).fold(along(&min), |a, b| a.max(b));
+                if high > low {
+                    (along(p) - low) / (high - low)
+                } else {
+                    F::one()
+                }
+            }
+            Shading::Function(density) => density(*p),
+        };
+        poisson_disk(min, max, radius, o)
+            .into_iter()
+            .filter(|p| region.contains(p))
+            .filter(|p| {
+                let keep = (self.density * shade(p)).max(F::zero()).min(F::one());
+                _cc::<F>(o.random()) < keep
+            })
+            .collect()
+    }
+}
+
+impl<F: RealNumber> Default for StippleFiller<F> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Points in the box from `min` to `max` that are at least `radius` apart and
+/// leave no gap wider than twice that, by Bridson's algorithm.
+fn poisson_disk<F: RealNumber>(
+    min: Point2<F>,
+    max: Point2<F>,
+    radius: F,
+    o: &mut DrawOptions,
+) -> Vec<Point2<F>> {
+    let cell = radius / _c::<F>(2.0).sqrt();
+    let columns = (_to_f64((max.x - min.x) / cell).floor() as usize) + 1;
+    let rows = (_to_f64((max.y - min.y) / cell).floor() as usize) + 1;
+    let mut grid: Vec<Option<usize>> = vec![None; columns * rows];
+    let index = |p: &Point2<F>| {
+        let column = (_to_f64((p.x - min.x) / cell) as usize).min(columns - 1);
+        let row = (_to_f64((p.y - min.y) / cell) as usize).min(rows - 1);
+        (column, row)
+    };
+
+    let start = Point2::new(
+        min.x + (max.x - min.x) * _cc::<F>(o.random()),
+        min.y + (max.y - min.y) * _cc::<F>(o.random()),
+    );
+    let (column, row) = index(&start);
+    grid[row * columns + column] = Some(0);
+    let mut points = vec![start];
+    let mut active = vec![0];
+    while !active.is_empty() {
+        let chosen = ((o.random() * active.len() as f64) as usize).min(active.len() - 1);
+        let base = points[active[chosen]];
+        let mut found = false;
+        for _ in 0..CANDIDATES {
+            let angle = get_pi::<F>() * _c(2.0) * _cc::<F>(o.random());
+            let distance = radius * (F::one() + _cc::<F>(o.random()));
+            let candidate = base + Vector2::new(angle.cos(), angle.sin()) * distance;
+            if candidate.x < min.x
+                || candidate.x > max.x
+                || candidate.y < min.y
+                || candidate.y > max.y
+            {
+                continue;
+            }
+            let (column, row) = index(&candidate);
+            let near = (row.saturating_sub(2)..(row + 3).min(rows)).any(|r| {
+                (column.saturating_sub(2)..(column + 3).min(columns)).any(|c| {
+                    grid[r * columns + c]
+                        .is_some_and(|i| nalgebra::distance(&points[i], &candidate) < radius)
+                })
+            });
+            if !near {
+                grid[row * columns + column] = Some(points.len());
+                active.push(points.len());
+                points.push(candidate);
+                found = true;
+                break;
+            }
+        }
+        if !found {
+            active.swap_remove(chosen);
+        }
+    }
+    points
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::{Point2, Vector2};
+
+    use super::{Shading, StippleFiller};
+    use crate::graphics::drawable::DrawOptions;
+
+    fn square() -> Vec<Vec<Point2<f64>>> {
+        vec![vec![
+            Point2::new(0.0, 0.0),
+            Point2::new(100.0, 0.0),
+            Point2::new(100.0, 100.0),
+            Point2::new(0.0, 100.0),
+        ]]
+    }
+
+    #[test]
+    fn dots_keep_their_distance() {
+        let filler = StippleFiller::new().with_min_distance(5.0);
+        let points = filler.stipple_points(&square(), &mut DrawOptions::default());
+        // a Poisson-disk set covers the square densely
+        assert!(points.len() > 200);
+        for (i, a) in points.iter().enumerate() {
+            assert!(a.x >= 0.0 && a.x <= 100.0 && a.y >= 0.0 && a.y <= 100.0);
+            for b in points.iter().skip(i + 1) {
+                assert!(nalgebra::distance(a, b) >= 5.0);
+            }
+        }
+    }
+
+    #[test]
+    fn shading_controls_where_dots_go() {
+        let mut o = DrawOptions::default();
+        let gradient = StippleFiller::new()
+            .with_min_distance(4.0)
+            .with_shading(Shading::Gradient(Vector2::new(1.0, 0.0)));
+        let points = gradient.stipple_points(&square(), &mut o);
+        let left = points.iter().filter(|p| p.x < 50.0).count();
+        assert!(left * 2 < points.len() - left);
+
+        let top_only = StippleFiller::new()
+            .with_min_distance(4.0)
+            .with_density_fn(|p: Point2<f64>| if p.y < 50.0 { 1.0 } else { 0.0 });
+        let points = top_only.stipple_points(&square(), &mut o);
+        assert!(!points.is_empty());
+        assert!(points.iter().all(|p| p.y < 50.0));
+    }
+}
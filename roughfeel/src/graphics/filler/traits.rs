@@ -6,6 +6,9 @@ use nalgebra_glm::RealNumber;
 use crate::graphics::drawable::DrawOptions;
 use crate::graphics::drawable_ops::OpSet;
 
+/// Turns a list of polygons into the strokes of a fill pattern. Polygons after
+/// the first may be holes. Implementations return a `FillSketch` set, which
+/// backends stroke with the fill colour and `fill_weight`.
 pub trait PatternFiller<F: RealNumber, P: BorrowMut<Vec<Vec<Point2<F>>>>> {
     fn fill_polygons(&self, polygon_list: P, o: &mut DrawOptions) -> OpSet<F>;
 }
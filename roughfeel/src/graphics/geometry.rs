@@ -1,6 +1,9 @@
 // Reference from https://github.com/orhanbalci/rough-rs/blob/main/roughr/src/geometry.rs
 use nalgebra::{Point2, Rotation2, Vector2};
 use nalgebra_glm::RealNumber;
+use piet::kurbo::{Affine, Point};
+
+use super::{_cc, _to_f64};
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct Line<F: RealNumber> {
@@ -24,8 +27,7 @@ pub struct BezierCubic<F: RealNumber> {
 }
 
 impl<F: RealNumber> Line<F> {
-    pub fn from(points: &[Point2<F>]) -> Self {
-        assert_eq!(points.len(), 2);
+    pub fn from(points: &[Point2<F>; 2]) -> Self {
         Line {
             start_point: points[0],
             end_point: points[1],
@@ -46,10 +48,30 @@ impl<F: RealNumber> Line<F> {
     }
 }
 
+/// Maps `p` through `affine`.
+pub(crate) fn transform_point<F: RealNumber>(affine: &Affine, p: &Point2<F>) -> Point2<F> {
+    let mapped = *affine * Point::new(_to_f64(p.x), _to_f64(p.y));
+    Point2::new(_cc(mapped.x), _cc(mapped.y))
+}
+
+/// How many times `ring` winds around `p`, positive for rings going one way
+/// and negative for the other. Points on an edge may count either way.
+pub(crate) fn winding_number<F: RealNumber>(ring: &[Point2<F>], p: &Point2<F>) -> i32 {
+    let mut winding = 0;
+    for (i, a) in ring.iter().enumerate() {
+        let b = &ring[(i + 1) % ring.len()];
+        if (a.y > p.y) != (b.y > p.y) && p.x < a.x + (p.y - a.y) * (b.x - a.x) / (b.y - a.y) {
+            winding += if b.y > a.y { 1 } else { -1 };
+        }
+    }
+    winding
+}
+
 fn degree_to_radians<F: RealNumber>(degrees: F) -> F {
     degrees / F::from_f64(180.0 / 3.141592653589793238).unwrap()
 }
 
+/// Rotates `points` by `degrees` around `center`.
 pub fn rotate_points<F: RealNumber>(
     points: &[Point2<F>],
     center: &Point2<F>,
@@ -1,14 +1,26 @@
+use nalgebra::ComplexField;
 use nalgebra_glm::RealNumber;
 
+use crate::error::Error;
+
+pub mod arrow;
+pub mod brush;
 pub mod drawable;
 pub mod drawable_maker;
 pub mod drawable_ops;
-mod filler;
+pub mod filler;
 mod geometry;
+pub mod occlusion;
+pub mod optimizer;
 pub mod paint;
+pub mod path_builder;
+pub mod path_data;
 pub mod points_on_path;
+pub mod raster;
 pub mod render_context;
 pub mod renderer;
+#[cfg(feature = "text")]
+pub mod text;
 
 use std::{f32, f64};
 
@@ -24,6 +36,14 @@ pub fn _cc<U: RealNumber>(inp: f64) -> U {
     U::from_f64(inp).expect("can not parse from f64")
 }
 
+/// Converts `inp` to `U`, failing when it does not fit. Finite values that
+/// overflow `U` to an infinity, such as `1e39` for `f32`, are rejected too.
+pub fn _try_cc<U: RealNumber>(inp: f64) -> Result<U, Error> {
+    U::from_f64(inp)
+        .filter(|v| !inp.is_finite() || ComplexField::is_finite(v))
+        .ok_or_else(|| Error::NumericConversion(format!("can not parse {} from f64", inp)))
+}
+
 pub fn _to_u64<U: RealNumber>(inp: U) -> u64 {
     _to_f64(inp) as u64
 }
@@ -32,6 +52,11 @@ pub fn _to_f64<U: RealNumber>(inp: U) -> f64 {
     nalgebra::try_convert(inp).expect("cannot convert to f64")
 }
 
+pub fn _try_to_f64<U: RealNumber>(inp: U) -> Result<f64, Error> {
+    nalgebra::try_convert(inp)
+        .ok_or_else(|| Error::NumericConversion("cannot convert to f64".into()))
+}
+
 pub fn _to_f32<U: RealNumber>(inp: U) -> f32 {
     nalgebra::try_convert(inp).unwrap() as f32
 }
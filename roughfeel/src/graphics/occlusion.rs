@@ -0,0 +1,255 @@
+use std::fmt::Display;
+
+use nalgebra::{Point2, Vector2};
+use nalgebra_glm::RealNumber;
+
+use super::_c;
+use super::drawable::RoughlyDrawable;
+use super::drawable_ops::{Bounds, Op, OpSet, OpType};
+use super::paint::FillRule;
+
+/// Removes the parts of strokes that are hidden under the fill of a drawable
+/// later in the list, as a raster backend would paint over them. Only filled
+/// drawables occlude, using their `fill_region`. Outlines and fill sketches are
+/// clipped; `FillPath` and `BrushPath` sets are left untouched since backends
+/// paint them in order anyway. Curves that need clipping are flattened within `tolerance`.
+pub fn remove_hidden_lines<F>(drawables: &mut [RoughlyDrawable<OpSet<F>>], tolerance: F)
+where
+    F: RealNumber + Display,
+{
+    let mut occluders: Vec<Occluder<F>> = vec![];
+    for drawable in drawables.iter_mut().rev() {
+        if !occluders.is_empty() {
+            drawable
+                .opsets
+                .iter_mut()
+                .filter(|set| !set.op_set_type.is_filled())
+                .for_each(|set| clip_set(set, &occluders, tolerance));
+        }
+        let rule = drawable.options.fill_rule.unwrap_or_default();
+        if let Some(occluder) = Occluder::new(&drawable.fill_region, rule) {
+            occluders.push(occluder);
+        }
+    }
+}
+
+/// A filled region, tested with the fill rule of its drawable so holes are
+/// respected.
+struct Occluder<F: RealNumber> {
+    rings: Vec<Vec<Point2<F>>>,
+    rule: FillRule,
+    min: Point2<F>,
+    max: Point2<F>,
+}
+
+impl<F: RealNumber> Occluder<F> {
+    fn new(region: &[Vec<Point2<F>>], rule: FillRule) -> Option<Self> {
+        let rings: Vec<Vec<Point2<F>>> = region.iter().filter(|r| r.len() > 2).cloned().collect();
+        let Bounds { min, max } = Bounds::of_points(rings.iter().flatten())?;
+        Some(Occluder {
+            rings,
+            rule,
+            min,
+            max,
+        })
+    }
+
+    fn overlaps(&self, a: &Point2<F>, b: &Point2<F>) -> bool {
+        a.x.max(b.x) >= self.min.x
+            && a.x.min(b.x) <= self.max.x
+            && a.y.max(b.y) >= self.min.y
+            && a.y.min(b.y) <= self.max.y
+    }
+
+    fn contains(&self, p: &Point2<F>) -> bool {
+        if !self.overlaps(p, p) {
+            return false;
+        }
+        self.rule.contains(&self.rings, p)
+    }
+
+    /// Pushes the parameters along `a`-`b` where it crosses an edge of the region.
+    fn crossings(&self, a: &Point2<F>, b: &Point2<F>, ts: &mut Vec<F>) {
+        let ab = b - a;
+        for ring in self.rings.iter() {
+            for (i, c) in ring.iter().enumerate() {
+                let cd = ring[(i + 1) % ring.len()] - c;
+                let denominator = cross(&ab, &cd);
+                if denominator == F::zero() {
+                    continue;
+                }
+                let ac = c - a;
+                let t = cross(&ac, &cd) / denominator;
+                let u = cross(&ac, &ab) / denominator;
+                if t > F::zero() && t < F::one() && u >= F::zero() && u <= F::one() {
+                    ts.push(t);
+                }
+            }
+        }
+    }
+}
+
+fn cross<F: RealNumber>(a: &Vector2<F>, b: &Vector2<F>) -> F {
+    a.x * b.y - a.y * b.x
+}
+
+/// Clips each subpath of `set`. Subpaths that are fully visible keep their
+/// original ops, the others are replaced by their visible polyline pieces.
+fn clip_set<F>(set: &mut OpSet<F>, occluders: &[Occluder<F>], tolerance: F)
+where
+    F: RealNumber + Display,
+{
+    let mut ops = vec![];
+    for subpath in split_subpaths(&set.ops) {
+        let flattened = OpSet {
+            op_set_type: set.op_set_type.clone(),
+            ops: subpath.clone(),
+            size: None,
+            path: None,
+        }
+        .to_polylines(tolerance);
+        let mut hidden = false;
+        let mut pieces = vec![];
+        for polyline in flattened.iter() {
+            hidden |= visible_pieces(polyline, occluders, &mut pieces);
+        }
+        if !hidden {
+            ops.extend(subpath);
+            continue;
+        }
+        for piece in pieces {
+            ops.push(Op {
+                op: OpType::Move,
+                data: vec![piece[0].x, piece[0].y],
+            });
+            ops.extend(piece.iter().skip(1).map(|p| Op {
+                op: OpType::LineTo,
+                data: vec![p.x, p.y],
+            }));
+        }
+    }
+    set.ops = ops;
+}
+
+fn split_subpaths<F: RealNumber>(ops: &[Op<F>]) -> Vec<Vec<Op<F>>> {
+    let mut subpaths: Vec<Vec<Op<F>>> = vec![];
+    for item in ops.iter() {
+        match subpaths.last_mut() {
+            Some(current) if item.op != OpType::Move => current.push(item.clone()),
+            _ => subpaths.push(vec![item.clone()]),
+        }
+    }
+    subpaths
+}
+
+/// Appends the visible pieces of `polyline` to `pieces` and returns whether
+/// any part of it was hidden.
+fn visible_pieces<F: RealNumber>(
+    polyline: &[Point2<F>],
+    occluders: &[Occluder<F>],
+    pieces: &mut Vec<Vec<Point2<F>>>,
+) -> bool {
+    let epsilon = _c::<F>(1.0e-9);
+    let mut hidden = false;
+    let mut current: Vec<Point2<F>> = vec![];
+    for segment in polyline.windows(2) {
+        let (a, b) = (segment[0], segment[1]);
+        let nearby: Vec<&Occluder<F>> = occluders.iter().filter(|o| o.overlaps(&a, &b)).collect();
+        let mut ts = vec![F::zero(), F::one()];
+        nearby.iter().for_each(|o| o.crossings(&a, &b, &mut ts));
+        ts.sort_by(|x, y| x.partial_cmp(y).unwrap());
+        for t in ts.windows(2) {
+            if t[1] - t[0] <= epsilon {
+                continue;
+            }
+            let middle = a + (b - a) * ((t[0] + t[1]) / _c(2.0));
+            if nearby.iter().any(|o| o.contains(&middle)) {
+                hidden = true;
+                if current.len() > 1 {
+                    pieces.push(std::mem::take(&mut current));
+                }
+                current.clear();
+            } else {
+                if current.is_empty() {
+                    current.push(a + (b - a) * t[0]);
+                }
+                current.push(a + (b - a) * t[1]);
+            }
+        }
+    }
+    if current.len() > 1 {
+        pieces.push(current);
+    }
+    hidden
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::Point2;
+    use palette::Srgba;
+
+    use super::remove_hidden_lines;
+    use crate::graphics::drawable::{DrawOptions, DrawOptionsBuilder};
+    use crate::graphics::drawable_maker::{Generator, RoughlyDrawableMakable};
+    use crate::graphics::drawable_ops::{Op, OpSet, OpSetType, OpType};
+    use crate::graphics::paint::FillStyle;
+
+    fn filled() -> Option<DrawOptions> {
+        Some(
+            DrawOptionsBuilder::default()
+                .fill(Srgba::new(0.0, 0.0, 0.0, 1.0))
+                .fill_style(FillStyle::Hachure)
+                .build()
+                .unwrap(),
+        )
+    }
+
+    #[test]
+    fn line_through_square_is_split() {
+        let generator = Generator::<OpSet<f64>>::default();
+        let mut line = generator.line(0.0, 50.0, 100.0, 50.0, &None);
+        line.opsets[0].ops = vec![
+            Op {
+                op: OpType::Move,
+                data: vec![0.0, 50.0],
+            },
+            Op {
+                op: OpType::LineTo,
+                data: vec![100.0, 50.0],
+            },
+        ];
+        let square = generator.rectangle(25.0, 0.0, 50.0, 100.0, &filled());
+        let mut drawables = vec![line, square];
+        remove_hidden_lines(&mut drawables, 0.5);
+
+        let polylines = drawables[0].opsets[0].to_polylines(0.5);
+        assert_eq!(
+            polylines,
+            vec![
+                vec![Point2::new(0.0, 50.0), Point2::new(25.0, 50.0)],
+                vec![Point2::new(75.0, 50.0), Point2::new(100.0, 50.0)],
+            ]
+        );
+    }
+
+    #[test]
+    fn only_later_filled_shapes_occlude() {
+        let generator = Generator::<OpSet<f64>>::default();
+        let below = generator.rectangle(0.0, 0.0, 100.0, 100.0, &filled());
+        let above = generator.circle(100.0, 100.0, 80.0, &filled());
+        let outline_only = generator.rectangle(10.0, 10.0, 30.0, 30.0, &None);
+        let mut drawables = vec![below.clone(), above.clone(), outline_only];
+        remove_hidden_lines(&mut drawables, 0.5);
+
+        assert_eq!(drawables[1].opsets, above.opsets);
+        assert_ne!(drawables[0].opsets, below.opsets);
+        // the jittered circle radius stays well above 35
+        let inside = |p: &Point2<f64>| nalgebra::distance(p, &Point2::new(100.0, 100.0)) < 35.0;
+        for set in drawables[0].opsets.iter() {
+            assert_ne!(set.op_set_type, OpSetType::FillPath);
+            for polyline in set.to_polylines(0.5) {
+                assert!(!polyline.iter().any(inside));
+            }
+        }
+    }
+}
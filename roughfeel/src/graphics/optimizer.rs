@@ -0,0 +1,450 @@
+use std::fmt::Display;
+
+use derive_builder::Builder;
+use nalgebra::{distance, distance_squared, Point2};
+use nalgebra_glm::RealNumber;
+
+use super::drawable::RoughlyDrawable;
+use super::drawable_ops::{Op, OpSet, OpSetType, OpType};
+use super::{_c, _cc, _to_f64};
+
+/// How strokes are reordered to cut down pen up travel.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StrokeOrdering {
+    /// Keep the generation order.
+    Keep,
+    /// Always continue with the closest remaining stroke.
+    NearestNeighbor,
+    /// Nearest neighbour, then improved with 2-opt moves. 2-opt reverses runs
+    /// of strokes, so it needs `allow_reverse`; without it this behaves like
+    /// `NearestNeighbor`.
+    TwoOpt,
+}
+
+#[derive(Clone, Debug, Builder)]
+#[builder(setter(strip_option))]
+pub struct OptimizerOptions {
+    #[builder(default = "StrokeOrdering::NearestNeighbor")]
+    pub ordering: StrokeOrdering,
+    /// Whether strokes may be drawn backwards when that shortens the travel.
+    #[builder(default = "true")]
+    pub allow_reverse: bool,
+    /// Strokes that start at most this far from where the previous one ended
+    /// are joined into a single pen down stroke. `None` never joins.
+    #[builder(default = "Some(0.0)")]
+    pub join_tolerance: Option<f64>,
+    /// Maximum number of passes of 2-opt improvement.
+    #[builder(default = "20")]
+    pub two_opt_passes: usize,
+    /// Tolerance for flattening curves when measuring draw distance.
+    #[builder(default = "0.5")]
+    pub tolerance: f64,
+}
+
+impl Default for OptimizerOptions {
+    fn default() -> Self {
+        OptimizerOptionsBuilder::default()
+            .build()
+            .expect("failed to build default optimizer options")
+    }
+}
+
+/// Pen statistics of a sequence of strokes, starting with the pen at the origin.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct PathStats {
+    pub strokes: usize,
+    pub draw_distance: f64,
+    pub travel_distance: f64,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct OptimizerReport {
+    pub before: PathStats,
+    pub after: PathStats,
+}
+
+/// Optimizes the strokes of each op set in place, continuing from where the
+/// pen left the previous set. `FillPath` sets are not pen strokes and are left
+/// untouched.
+pub fn optimize_opsets<F>(sets: &mut [OpSet<F>], options: &OptimizerOptions) -> OptimizerReport
+where
+    F: RealNumber + Display,
+{
+    let mut state = PenState::new();
+    sets.iter_mut()
+        .for_each(|set| state.optimize_set(set, options));
+    state.report
+}
+
+/// Optimizes a list of drawables in place, keeping their order. Within each
+/// drawable the op sets of the same type share a style, so they are merged
+/// into one before their strokes are reordered.
+pub fn optimize_drawables<F>(
+    drawables: &mut [RoughlyDrawable<OpSet<F>>],
+    options: &OptimizerOptions,
+) -> OptimizerReport
+where
+    F: RealNumber + Display,
+{
+    let mut state = PenState::new();
+    for drawable in drawables.iter_mut() {
+        let mut merged: Vec<OpSet<F>> = vec![];
+        for set in drawable.opsets.drain(..) {
+            let existing = merged
+                .iter_mut()
+                .find(|m| m.op_set_type == set.op_set_type && !set.op_set_type.is_filled());
+            match existing {
+                Some(m) => m.ops.extend(set.ops),
+                None => merged.push(set),
+            }
+        }
+        merged
+            .iter_mut()
+            .for_each(|set| state.optimize_set(set, options));
+        drawable.opsets = merged;
+    }
+    state.report
+}
+
+struct PenState<F: RealNumber> {
+    before: Point2<F>,
+    after: Point2<F>,
+    report: OptimizerReport,
+}
+
+impl<F: RealNumber + Display> PenState<F> {
+    fn new() -> Self {
+        PenState {
+            before: Point2::origin(),
+            after: Point2::origin(),
+            report: OptimizerReport::default(),
+        }
+    }
+
+    fn optimize_set(&mut self, set: &mut OpSet<F>, options: &OptimizerOptions) {
+        if set.op_set_type.is_filled() {
+            return;
+        }
+        let tolerance = _cc::<F>(options.tolerance);
+        let strokes = split_strokes(&set.ops);
+        self.before = add_stats(&mut self.report.before, &strokes, self.before, tolerance);
+
+        let allow_reverse = options.allow_reverse;
+        let mut order = match options.ordering {
+            StrokeOrdering::Keep => (0..strokes.len()).map(|i| (i, false)).collect(),
+            _ => nearest_neighbor(&strokes, self.after, allow_reverse),
+        };
+        if options.ordering == StrokeOrdering::TwoOpt && allow_reverse {
+            two_opt(&strokes, &mut order, self.after, options.two_opt_passes);
+        }
+        set.ops = assemble(&strokes, &order, options.join_tolerance.map(_cc::<F>));
+
+        let optimized = split_strokes(&set.ops);
+        self.after = add_stats(&mut self.report.after, &optimized, self.after, tolerance);
+    }
+}
+
+/// Splits ops into strokes, each starting with a move. Moves that are not
+/// followed by drawing are dropped.
+fn split_strokes<F: RealNumber>(ops: &[Op<F>]) -> Vec<Vec<Op<F>>> {
+    let mut strokes = vec![];
+    let mut current: Vec<Op<F>> = vec![];
+    for item in ops.iter() {
+        if item.op == OpType::Move {
+            if current.len() > 1 {
+                strokes.push(std::mem::take(&mut current));
+            }
+            current.clear();
+        } else if current.is_empty() {
+            current.push(Op {
+                op: OpType::Move,
+                data: vec![_c(0.0), _c(0.0)],
+            });
+        }
+        current.push(item.clone());
+    }
+    if current.len() > 1 {
+        strokes.push(current);
+    }
+    strokes
+}
+
+fn end_point<F: RealNumber>(item: &Op<F>) -> Point2<F> {
+    let n = item.data.len();
+    Point2::new(item.data[n - 2], item.data[n - 1])
+}
+
+fn stroke_start<F: RealNumber>(stroke: &[Op<F>]) -> Point2<F> {
+    end_point(&stroke[0])
+}
+
+fn stroke_end<F: RealNumber>(stroke: &[Op<F>]) -> Point2<F> {
+    end_point(&stroke[stroke.len() - 1])
+}
+
+/// The same stroke drawn from its end back to its start.
+fn reverse_stroke<F: RealNumber>(stroke: &[Op<F>]) -> Vec<Op<F>> {
+    let end = stroke_end(stroke);
+    let mut reversed = vec![Op {
+        op: OpType::Move,
+        data: vec![end.x, end.y],
+    }];
+    for i in (1..stroke.len()).rev() {
+        let from = end_point(&stroke[i - 1]);
+        let d = &stroke[i].data;
+        reversed.push(match stroke[i].op {
+            OpType::BCurveTo => Op {
+                op: OpType::BCurveTo,
+                data: vec![d[2], d[3], d[0], d[1], from.x, from.y],
+            },
+            _ => Op {
+                op: OpType::LineTo,
+                data: vec![from.x, from.y],
+            },
+        });
+    }
+    reversed
+}
+
+fn oriented_start<F: RealNumber>(
+    strokes: &[Vec<Op<F>>],
+    (i, reversed): (usize, bool),
+) -> Point2<F> {
+    if reversed {
+        stroke_end(&strokes[i])
+    } else {
+        stroke_start(&strokes[i])
+    }
+}
+
+fn oriented_end<F: RealNumber>(strokes: &[Vec<Op<F>>], (i, reversed): (usize, bool)) -> Point2<F> {
+    oriented_start(strokes, (i, !reversed))
+}
+
+fn nearest_neighbor<F: RealNumber>(
+    strokes: &[Vec<Op<F>>],
+    start: Point2<F>,
+    allow_reverse: bool,
+) -> Vec<(usize, bool)> {
+    let mut remaining: Vec<usize> = (0..strokes.len()).collect();
+    let mut order = Vec::with_capacity(strokes.len());
+    let mut pen = start;
+    while !remaining.is_empty() {
+        let mut best = (0, false);
+        let mut best_distance = distance_squared(&pen, &stroke_start(&strokes[remaining[0]]));
+        for (k, &i) in remaining.iter().enumerate() {
+            let d = distance_squared(&pen, &stroke_start(&strokes[i]));
+            if d < best_distance {
+                best = (k, false);
+                best_distance = d;
+            }
+            if allow_reverse {
+                let d = distance_squared(&pen, &stroke_end(&strokes[i]));
+                if d < best_distance {
+                    best = (k, true);
+                    best_distance = d;
+                }
+            }
+        }
+        let next = (remaining.swap_remove(best.0), best.1);
+        pen = oriented_end(strokes, next);
+        order.push(next);
+    }
+    order
+}
+
+/// Improves the order with 2-opt moves, which draw a run of strokes in the
+/// opposite order and direction. Only the travel into and out of the run
+/// changes, so every move is evaluated in constant time.
+fn two_opt<F: RealNumber>(
+    strokes: &[Vec<Op<F>>],
+    order: &mut [(usize, bool)],
+    start: Point2<F>,
+    passes: usize,
+) {
+    let n = order.len();
+    let epsilon = _c::<F>(1.0e-6);
+    for _ in 0..passes {
+        let mut improved = false;
+        for i in 0..n {
+            for j in i..n {
+                let before = if i == 0 {
+                    start
+                } else {
+                    oriented_end(strokes, order[i - 1])
+                };
+                let first_start = oriented_start(strokes, order[i]);
+                let last_end = oriented_end(strokes, order[j]);
+                let mut current = distance(&before, &first_start);
+                let mut candidate = distance(&before, &last_end);
+                if j + 1 < n {
+                    let after = oriented_start(strokes, order[j + 1]);
+                    current += distance(&last_end, &after);
+                    candidate += distance(&first_start, &after);
+                }
+                if candidate + epsilon < current {
+                    order[i..=j].reverse();
+                    order[i..=j]
+                        .iter_mut()
+                        .for_each(|(_, reversed)| *reversed = !*reversed);
+                    improved = true;
+                }
+            }
+        }
+        if !improved {
+            break;
+        }
+    }
+}
+
+fn assemble<F: RealNumber>(
+    strokes: &[Vec<Op<F>>],
+    order: &[(usize, bool)],
+    join_tolerance: Option<F>,
+) -> Vec<Op<F>> {
+    let mut ops: Vec<Op<F>> = vec![];
+    for &(i, reversed) in order.iter() {
+        let stroke = if reversed {
+            reverse_stroke(&strokes[i])
+        } else {
+            strokes[i].clone()
+        };
+        let start = stroke_start(&stroke);
+        let joined = match (join_tolerance, ops.last()) {
+            (Some(tolerance), Some(last)) => distance(&end_point(last), &start) <= tolerance,
+            _ => false,
+        };
+        if joined {
+            if end_point(ops.last().unwrap()) != start {
+                ops.push(Op {
+                    op: OpType::LineTo,
+                    data: vec![start.x, start.y],
+                });
+            }
+            ops.extend(stroke.into_iter().skip(1));
+        } else {
+            ops.extend(stroke);
+        }
+    }
+    ops
+}
+
+/// Adds the stats of drawing `strokes` from `pen` and returns where the pen ends.
+fn add_stats<F: RealNumber + Display>(
+    stats: &mut PathStats,
+    strokes: &[Vec<Op<F>>],
+    mut pen: Point2<F>,
+    tolerance: F,
+) -> Point2<F> {
+    for stroke in strokes.iter() {
+        stats.strokes += 1;
+        stats.travel_distance += _to_f64(distance(&pen, &stroke_start(stroke)));
+        let set = OpSet {
+            op_set_type: OpSetType::Path,
+            ops: stroke.clone(),
+            size: None,
+            path: None,
+        };
+        for polyline in set.to_polylines(tolerance) {
+            stats.draw_distance += polyline
+                .windows(2)
+                .map(|w| _to_f64(distance(&w[0], &w[1])))
+                .sum::<f64>();
+        }
+        pen = stroke_end(stroke);
+    }
+    pen
+}
+
+#[cfg(test)]
+mod tests {
+    use palette::Srgba;
+
+    use super::{
+        optimize_drawables, optimize_opsets, reverse_stroke, split_strokes, OptimizerOptions,
+        OptimizerOptionsBuilder, StrokeOrdering,
+    };
+    use crate::graphics::drawable::DrawOptionsBuilder;
+    use crate::graphics::drawable_maker::{Generator, RoughlyDrawableMakable};
+    use crate::graphics::drawable_ops::{Op, OpSet, OpSetType, OpType};
+    use crate::graphics::paint::FillStyle;
+
+    fn op(op: OpType, data: Vec<f64>) -> Op<f64> {
+        Op { op, data }
+    }
+
+    #[test]
+    fn reversed_stroke_traces_the_same_points() {
+        let stroke = vec![
+            op(OpType::Move, vec![0.0, 0.0]),
+            op(OpType::LineTo, vec![10.0, 0.0]),
+            op(OpType::BCurveTo, vec![15.0, 0.0, 20.0, 5.0, 20.0, 10.0]),
+        ];
+        assert_eq!(
+            reverse_stroke(&stroke),
+            vec![
+                op(OpType::Move, vec![20.0, 10.0]),
+                op(OpType::BCurveTo, vec![20.0, 5.0, 15.0, 0.0, 10.0, 0.0]),
+                op(OpType::LineTo, vec![0.0, 0.0]),
+            ]
+        );
+        assert_eq!(reverse_stroke(&reverse_stroke(&stroke)), stroke);
+    }
+
+    #[test]
+    fn touching_strokes_are_joined() {
+        let mut sets = vec![OpSet {
+            op_set_type: OpSetType::Path,
+            ops: vec![
+                op(OpType::Move, vec![20.0, 0.0]),
+                op(OpType::LineTo, vec![10.0, 0.0]),
+                op(OpType::Move, vec![0.0, 0.0]),
+                op(OpType::LineTo, vec![10.0, 0.0]),
+            ],
+            size: None,
+            path: None,
+        }];
+        let report = optimize_opsets(&mut sets, &OptimizerOptions::default());
+        assert_eq!(split_strokes(&sets[0].ops).len(), 1);
+        assert_eq!(report.before.strokes, 2);
+        assert_eq!(report.after.strokes, 1);
+        assert_eq!(report.before.travel_distance, 30.0);
+        assert_eq!(report.after.travel_distance, 0.0);
+        assert_eq!(report.before.draw_distance, report.after.draw_distance);
+    }
+
+    #[test]
+    fn hachure_travel_is_reduced() {
+        let options = DrawOptionsBuilder::default()
+            .fill(Srgba::new(0.0, 0.0, 0.0, 1.0))
+            .fill_style(FillStyle::CrossHatch)
+            .build()
+            .unwrap();
+        let generator = Generator::<OpSet<f64>>::new(options);
+        let shapes = vec![
+            generator.rectangle(10.0, 10.0, 200.0, 120.0, &None),
+            generator.circle(300.0, 200.0, 150.0, &None),
+        ];
+
+        let mut nearest = shapes.clone();
+        let nearest_report = optimize_drawables(&mut nearest, &OptimizerOptions::default());
+        let mut two_opt = shapes;
+        let two_opt_options = OptimizerOptionsBuilder::default()
+            .ordering(StrokeOrdering::TwoOpt)
+            .build()
+            .unwrap();
+        let two_opt_report = optimize_drawables(&mut two_opt, &two_opt_options);
+
+        let before = nearest_report.before;
+        assert!(nearest_report.after.travel_distance < before.travel_distance / 2.0);
+        assert!(two_opt_report.after.travel_distance <= nearest_report.after.travel_distance);
+        for report in [nearest_report, two_opt_report] {
+            let draw_change = (report.after.draw_distance - before.draw_distance).abs();
+            assert!(draw_change < before.draw_distance * 0.01);
+        }
+        // fill sketch and outline sets are merged, keeping fill below outline
+        assert!(nearest.iter().all(|d| d.opsets.len() == 2
+            && d.opsets[0].op_set_type == OpSetType::FillSketch
+            && d.opsets[1].op_set_type == OpSetType::Path));
+    }
+}
@@ -1,4 +1,10 @@
+use nalgebra::Point2;
+use nalgebra_glm::RealNumber;
+
+use super::geometry::winding_number;
+
 #[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum FillStyle {
     Solid,
     Hachure,
@@ -7,9 +13,53 @@ pub enum FillStyle {
     Dots,
     Dashed,
     ZigZagLine,
+    /// Rings offset inwards from the outline, `hachure_gap` apart.
+    Contour,
+    /// One spiral from the centre outwards, turns `hachure_gap` apart.
+    Spiral,
+    /// Hachure drawn back and forth in one stroke.
+    Scribble,
+    /// Randomly scattered dots at least `hachure_gap` apart, evenly dense.
+    /// To set the density, shading or spacing, register a configured
+    /// `StippleFiller` with `Generator::register_filler` and draw with
+    /// `FillStyle::Custom` under its name.
+    Stipple,
+    /// A filler registered on the generator under this name. Shapes fall back
+    /// to hachure when no filler has been registered for it.
+    Custom(String),
+}
+
+/// Decides which parts of a shape with overlapping or nested contours are
+/// inside it, for solid fills and pattern fillers alike.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FillRule {
+    /// Inside wherever the contours wind around a point, so overlapping
+    /// contours drawn in the same direction stay filled.
+    NonZero,
+    /// Inside wherever a ray from a point crosses an odd number of edges, so
+    /// nested contours alternate between filled and holes.
+    #[default]
+    EvenOdd,
+}
+
+impl FillRule {
+    /// Whether a point the contours wind around `winding` times is inside.
+    pub fn is_inside(&self, winding: i32) -> bool {
+        match self {
+            FillRule::NonZero => winding != 0,
+            FillRule::EvenOdd => winding % 2 != 0,
+        }
+    }
+
+    /// Whether `p` is inside the area of `polygons` under this rule.
+    pub fn contains<F: RealNumber>(&self, polygons: &[Vec<Point2<F>>], p: &Point2<F>) -> bool {
+        self.is_inside(polygons.iter().map(|ring| winding_number(ring, p)).sum())
+    }
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum LineCap {
     Butt,
     Round,
@@ -18,6 +68,7 @@ pub enum LineCap {
 
 /// Options for angled joins in strokes.
 #[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum LineJoin {
     Miter { limit: f64 },
     Round,
@@ -33,3 +84,32 @@ impl Default for LineJoin {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::Point2;
+
+    use super::FillRule;
+
+    #[test]
+    fn rules_differ_where_contours_overlap() {
+        let square = |a: f64, b: f64| {
+            vec![
+                Point2::new(a, a),
+                Point2::new(b, a),
+                Point2::new(b, b),
+                Point2::new(a, b),
+            ]
+        };
+        let nested = vec![square(0.0, 10.0), square(3.0, 7.0)];
+        let centre = Point2::new(5.0, 5.0);
+        assert!(!FillRule::EvenOdd.contains(&nested, &centre));
+        assert!(FillRule::NonZero.contains(&nested, &centre));
+
+        let mut reversed = square(3.0, 7.0);
+        reversed.reverse();
+        let hole = vec![square(0.0, 10.0), reversed];
+        assert!(!FillRule::NonZero.contains(&hole, &centre));
+        assert!(FillRule::NonZero.contains(&hole, &Point2::new(1.0, 5.0)));
+    }
+}
@@ -0,0 +1,200 @@
+use std::marker::PhantomData;
+
+use nalgebra_glm::RealNumber;
+use svgtypes::PathSegment;
+
+use super::_to_f64;
+
+/// Builds a path in code for [`RoughlyDrawableMakable::path_from_builder`],
+/// instead of formatting SVG path data for [`RoughlyDrawableMakable::path`].
+///
+/// Every command has the semantics of its absolute SVG counterpart. A path can
+/// hold several subpaths, each started with [`RoughPathBuilder::move_to`];
+/// fills follow [`DrawOptions::fill_rule`], so under the default even-odd
+/// rule inner subpaths become holes.
+///
+/// [`DrawOptions::fill_rule`]: super::drawable::DrawOptions::fill_rule
+///
+/// [`RoughlyDrawableMakable::path_from_builder`]: super::drawable_maker::RoughlyDrawableMakable::path_from_builder
+/// [`RoughlyDrawableMakable::path`]: super::drawable_maker::RoughlyDrawableMakable::path
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RoughPathBuilder<F: RealNumber> {
+    segments: Vec<PathSegment>,
+    phantom_data_f: PhantomData<F>,
+}
+
+impl<F: RealNumber> RoughPathBuilder<F> {
+    pub fn new() -> Self {
+        RoughPathBuilder {
+            segments: vec![],
+            phantom_data_f: PhantomData,
+        }
+    }
+
+    pub fn move_to(&mut self, x: F, y: F) -> &mut Self {
+        self.segments.push(PathSegment::MoveTo {
+            abs: true,
+            x: _to_f64(x),
+            y: _to_f64(y),
+        });
+        self
+    }
+
+    pub fn line_to(&mut self, x: F, y: F) -> &mut Self {
+        self.push(PathSegment::LineTo {
+            abs: true,
+            x: _to_f64(x),
+            y: _to_f64(y),
+        })
+    }
+
+    pub fn quad_to(&mut self, cpx: F, cpy: F, x: F, y: F) -> &mut Self {
+        self.push(PathSegment::Quadratic {
+            abs: true,
+            x1: _to_f64(cpx),
+            y1: _to_f64(cpy),
+            x: _to_f64(x),
+            y: _to_f64(y),
+        })
+    }
+
+    pub fn cubic_to(&mut self, cp1x: F, cp1y: F, cp2x: F, cp2y: F, x: F, y: F) -> &mut Self {
+        self.push(PathSegment::CurveTo {
+            abs: true,
+            x1: _to_f64(cp1x),
+            y1: _to_f64(cp1y),
+            x2: _to_f64(cp2x),
+            y2: _to_f64(cp2y),
+            x: _to_f64(x),
+            y: _to_f64(y),
+        })
+    }
+
+    /// Adds an elliptical arc to `(x, y)`, like the SVG `A` command.
+    /// `x_axis_rotation` is in degrees.
+    #[allow(clippy::too_many_arguments)]
+    pub fn arc_to(
+        &mut self,
+        rx: F,
+        ry: F,
+        x_axis_rotation: F,
+        large_arc: bool,
+        sweep: bool,
+        x: F,
+        y: F,
+    ) -> &mut Self {
+        self.push(PathSegment::EllipticalArc {
+            abs: true,
+            rx: _to_f64(rx),
+            ry: _to_f64(ry),
+            x_axis_rotation: _to_f64(x_axis_rotation),
+            large_arc,
+            sweep,
+            x: _to_f64(x),
+            y: _to_f64(y),
+        })
+    }
+
+    pub fn close(&mut self) -> &mut Self {
+        self.push(PathSegment::ClosePath { abs: true })
+    }
+
+    pub fn segments(&self) -> &[PathSegment] {
+        &self.segments
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.segments.is_empty()
+    }
+
+    fn push(&mut self, segment: PathSegment) -> &mut Self {
+        // SVG path data must start with a move, so start at the origin like a fresh path would
+        if self.segments.is_empty() {
+            self.segments.push(PathSegment::MoveTo {
+                abs: true,
+                x: 0.0,
+                y: 0.0,
+            });
+        }
+        self.segments.push(segment);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::Point2;
+    use palette::Srgba;
+
+    use super::RoughPathBuilder;
+    use crate::graphics::drawable::DrawOptionsBuilder;
+    use crate::graphics::drawable_maker::{Generator, RoughlyDrawableMakable};
+    use crate::graphics::drawable_ops::{OpSet, OpSetType, OpType};
+    use crate::graphics::paint::{FillRule, FillStyle};
+
+    #[test]
+    fn builder_matches_string_path() {
+        let options = DrawOptionsBuilder::default()
+            .fill(Srgba::new(0.0, 0.0, 0.0, 1.0))
+            .build()
+            .unwrap();
+        let generator = Generator::<OpSet<f64>>::new(options);
+        let mut builder = RoughPathBuilder::new();
+        builder
+            .move_to(10.0, 10.0)
+            .quad_to(40.0, 80.0, 90.0, 10.0)
+            .cubic_to(120.0, -20.0, 150.0, 60.0, 180.0, 30.0)
+            .arc_to(30.0, 20.0, 15.0, false, true, 120.0, 90.0)
+            .line_to(20.0, 90.0)
+            .close();
+        let d = "M10 10 Q40 80 90 10 C120 -20 150 60 180 30 A30 20 15 0 1 120 90 L20 90 Z";
+        assert_eq!(
+            generator.path_from_builder(&builder, &None).opsets,
+            generator.path(d.into(), &None).opsets
+        );
+    }
+
+    #[test]
+    fn subpaths_become_holes_of_solid_fill() {
+        let options = DrawOptionsBuilder::default()
+            .fill(Srgba::new(0.0, 0.0, 0.0, 1.0))
+            .fill_style(FillStyle::Solid)
+            .fill_rule(FillRule::EvenOdd)
+            .build()
+            .unwrap();
+        let generator = Generator::<OpSet<f32>>::new(options);
+        let mut builder = RoughPathBuilder::new();
+        builder
+            .move_to(0.0, 0.0)
+            .line_to(100.0, 0.0)
+            .line_to(100.0, 100.0)
+            .line_to(0.0, 100.0)
+            .close()
+            .move_to(25.0, 25.0)
+            .line_to(75.0, 25.0)
+            .line_to(75.0, 75.0)
+            .line_to(25.0, 75.0)
+            .close();
+        let drawable = generator.path_from_builder(&builder, &None);
+        let fill = drawable
+            .opsets
+            .iter()
+            .find(|set| set.op_set_type == OpSetType::FillPath)
+            .unwrap();
+        assert_eq!(
+            fill.ops.iter().filter(|op| op.op == OpType::Move).count(),
+            2
+        );
+        let polylines = fill.to_polylines(0.1);
+        assert!(FillRule::EvenOdd.contains(&polylines, &Point2::new(10.0, 10.0)));
+        assert!(!FillRule::EvenOdd.contains(&polylines, &Point2::new(50.0, 50.0)));
+        assert!(!drawable.hit_test(&Point2::new(50.0, 50.0), 1.0));
+    }
+
+    #[test]
+    fn drawing_without_move_starts_at_origin() {
+        let mut builder = RoughPathBuilder::<f32>::new();
+        builder.line_to(5.0, 5.0);
+        assert_eq!(builder.segments().len(), 2);
+    }
+}
@@ -0,0 +1,276 @@
+use std::fmt::{Display, Write};
+
+use nalgebra_glm::RealNumber;
+use piet::kurbo::PathEl;
+use svg_path_ops::{absolutize, normalize};
+use svgtypes::{PathParser, PathSegment};
+
+use super::drawable_ops::{Op, OpSet, OpSetType, OpType};
+use super::{_cc, _try_cc};
+use crate::error::Error;
+
+/// Serializes ops into SVG path data made of `M`, `L` and `C` commands.
+///
+/// Coordinates are written with the shortest representation that reads back
+/// into the same value, unless `fixed_decimals` rounds them first.
+pub fn ops_to_path_data<F>(ops: &[Op<F>], fixed_decimals: Option<u32>) -> String
+where
+    F: RealNumber + Display,
+{
+    let mut path = String::new();
+    for item in ops.iter() {
+        let data: Vec<F> = item
+            .data
+            .iter()
+            .map(|v| round_to_decimals(*v, fixed_decimals))
+            .collect();
+        if !path.is_empty() {
+            path.push(' ');
+        }
+        match item.op {
+            OpType::Move => write!(&mut path, "M{} {}", data[0], data[1]),
+            OpType::LineTo => write!(&mut path, "L{} {}", data[0], data[1]),
+            OpType::BCurveTo => write!(
+                &mut path,
+                "C{} {} {} {} {} {}",
+                data[0], data[1], data[2], data[3], data[4], data[5]
+            ),
+        }
+        .expect("Failed to write path string");
+    }
+    path
+}
+
+/// Parses SVG path data into segments, failing on the first malformed command
+/// instead of silently dropping the rest of the path.
+pub fn parse_path_segments(d: &str) -> Result<Vec<PathSegment>, Error> {
+    PathParser::from(d)
+        .collect::<Result<Vec<PathSegment>, _>>()
+        .map_err(|e| Error::from_path_data(d, e))
+}
+
+/// Parses SVG path data into ops.
+///
+/// Relative, shorthand and arc commands are normalized into move, line and
+/// cubic curve ops, and a close path becomes a line back to the start of its
+/// subpath.
+pub fn path_data_to_ops<F: RealNumber>(d: &str) -> Result<Vec<Op<F>>, Error> {
+    let path_segments = parse_path_segments(d)?;
+    let mut ops = vec![];
+    let mut first = (0.0, 0.0);
+    for segment in normalize(absolutize(path_segments.iter())) {
+        match segment {
+            PathSegment::MoveTo { x, y, .. } => {
+                first = (x, y);
+                ops.push(Op {
+                    op: OpType::Move,
+                    data: vec![_try_cc(x)?, _try_cc(y)?],
+                });
+            }
+            PathSegment::LineTo { x, y, .. } => ops.push(Op {
+                op: OpType::LineTo,
+                data: vec![_try_cc(x)?, _try_cc(y)?],
+            }),
+            PathSegment::CurveTo {
+                x1,
+                y1,
+                x2,
+                y2,
+                x,
+                y,
+                ..
+            } => ops.push(Op {
+                op: OpType::BCurveTo,
+                data: vec![
+                    _try_cc(x1)?,
+                    _try_cc(y1)?,
+                    _try_cc(x2)?,
+                    _try_cc(y2)?,
+                    _try_cc(x)?,
+                    _try_cc(y)?,
+                ],
+            }),
+            PathSegment::ClosePath { .. } => ops.push(Op {
+                op: OpType::LineTo,
+                data: vec![_try_cc(first.0)?, _try_cc(first.1)?],
+            }),
+            segment => return Err(Error::UnexpectedSegment(segment)),
+        }
+    }
+    Ok(ops)
+}
+
+/// Converts a kurbo path element into the equivalent absolute SVG path segment.
+pub fn path_element_to_segment(element: PathEl) -> PathSegment {
+    match element {
+        PathEl::MoveTo(p) => PathSegment::MoveTo {
+            abs: true,
+            x: p.x,
+            y: p.y,
+        },
+        PathEl::LineTo(p) => PathSegment::LineTo {
+            abs: true,
+            x: p.x,
+            y: p.y,
+        },
+        PathEl::QuadTo(p1, p) => PathSegment::Quadratic {
+            abs: true,
+            x1: p1.x,
+            y1: p1.y,
+            x: p.x,
+            y: p.y,
+        },
+        PathEl::CurveTo(p1, p2, p) => PathSegment::CurveTo {
+            abs: true,
+            x1: p1.x,
+            y1: p1.y,
+            x2: p2.x,
+            y2: p2.y,
+            x: p.x,
+            y: p.y,
+        },
+        PathEl::ClosePath => PathSegment::ClosePath { abs: true },
+    }
+}
+
+pub fn opset_to_path_data<F>(op_set: &OpSet<F>, fixed_decimals: Option<u32>) -> String
+where
+    F: RealNumber + Display,
+{
+    ops_to_path_data(&op_set.ops, fixed_decimals)
+}
+
+pub fn path_data_to_opset<F: RealNumber>(
+    d: &str,
+    op_set_type: OpSetType,
+) -> Result<OpSet<F>, Error> {
+    Ok(OpSet {
+        op_set_type,
+        ops: path_data_to_ops(d)?,
+        size: None,
+        path: None,
+    })
+}
+
+/// The most decimal places [`round_to_decimals`] rounds to. An `f64` holds
+/// no more than 17 significant digits, so more places change nothing.
+const MAX_FIXED_DECIMALS: u32 = 17;
+
+/// Rounds `value` to `fixed_decimals` decimal places, leaving it untouched for `None`.
+/// Values too large to scale by the power of ten are returned as they are.
+pub fn round_to_decimals<F: RealNumber>(value: F, fixed_decimals: Option<u32>) -> F {
+    match fixed_decimals {
+        Some(fd) => {
+            let pow = _cc::<F>(10f64.powi(fd.min(MAX_FIXED_DECIMALS) as i32));
+            let scaled = value * pow;
+            if scaled.is_finite() {
+                scaled.round() / pow
+            } else {
+                value
+            }
+        }
+        None => value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ops_to_path_data, path_data_to_ops, path_data_to_opset, round_to_decimals};
+    use crate::error::Error;
+    use crate::graphics::drawable_maker::{Generator, RoughlyDrawableMakable};
+    use crate::graphics::drawable_ops::{Op, OpSet, OpType};
+
+    #[test]
+    fn serializes_move_line_and_curve() {
+        let ops = vec![
+            Op {
+                op: OpType::Move,
+                data: vec![1.0, -2.5],
+            },
+            Op {
+                op: OpType::LineTo,
+                data: vec![3.0, 4.0],
+            },
+            Op {
+                op: OpType::BCurveTo,
+                data: vec![5.0, 6.0, 7.0, 8.0, 9.0, 10.0],
+            },
+        ];
+        assert_eq!(ops_to_path_data(&ops, None), "M1 -2.5 L3 4 C5 6 7 8 9 10");
+    }
+
+    #[test]
+    fn round_trip_f64() {
+        let generator = Generator::<OpSet<f64>>::default();
+        let ellipse = generator.ellipse(13.7, 21.3, 40.1, 30.9, &None);
+        for set in ellipse.opsets.iter() {
+            let d = ops_to_path_data(&set.ops, None);
+            let parsed = path_data_to_opset(&d, set.op_set_type.clone()).unwrap();
+            assert_eq!(*set, parsed);
+        }
+    }
+
+    #[test]
+    fn round_trip_f32() {
+        let generator = Generator::<OpSet<f32>>::default();
+        let path = generator.path("M10 10 Q 40 80 90 10 T 150 30 Z".into(), &None);
+        for set in path.opsets.iter() {
+            let d = ops_to_path_data(&set.ops, None);
+            assert_eq!(set.ops, path_data_to_ops::<f32>(&d).unwrap());
+        }
+    }
+
+    #[test]
+    fn round_trip_with_fixed_decimals() {
+        let generator = Generator::<OpSet<f64>>::default();
+        let line = generator.line(0.0, 0.0, 100.0, 50.0, &None);
+        let d = ops_to_path_data(&line.opsets[0].ops, Some(2));
+        let parsed = path_data_to_ops::<f64>(&d).unwrap();
+        assert_eq!(parsed.len(), line.opsets[0].ops.len());
+        assert_eq!(ops_to_path_data(&parsed, None), d);
+    }
+
+    #[test]
+    fn rounds_to_many_decimals() {
+        assert_eq!(
+            round_to_decimals(0.1234567890123456, Some(12)),
+            0.123456789012
+        );
+        assert_eq!(round_to_decimals(1.5f32, Some(12)), 1.5);
+        assert_eq!(round_to_decimals(1.0e300, Some(u32::MAX)), 1.0e300);
+    }
+
+    #[test]
+    fn parses_relative_commands_and_close_path() {
+        let ops = path_data_to_ops::<f64>("m 1 1 h 2 v 2 z").unwrap();
+        assert_eq!(
+            ops,
+            vec![
+                Op {
+                    op: OpType::Move,
+                    data: vec![1.0, 1.0]
+                },
+                Op {
+                    op: OpType::LineTo,
+                    data: vec![3.0, 1.0]
+                },
+                Op {
+                    op: OpType::LineTo,
+                    data: vec![3.0, 3.0]
+                },
+                Op {
+                    op: OpType::LineTo,
+                    data: vec![1.0, 1.0]
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_path_data() {
+        assert!(matches!(
+            path_data_to_ops::<f64>("M 1 1 L x"),
+            Err(Error::InvalidPathData { offset: 8, .. })
+        ));
+    }
+}
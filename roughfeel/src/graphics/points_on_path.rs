@@ -1,3 +1,4 @@
+use std::borrow::Borrow;
 use std::fmt::Display;
 
 use nalgebra::Point2;
@@ -6,8 +7,12 @@ use points_on_curve::{points_on_bezier_curves, simplify};
 use svg_path_ops::{absolutize, normalize};
 use svgtypes::{PathParser, PathSegment};
 
-use crate::graphics::{_c, _cc};
+use crate::error::Error;
+use crate::graphics::path_data::parse_path_segments;
+use crate::graphics::{_c, _try_cc};
 
+/// Samples points along an SVG path. Parsing stops at the first malformed
+/// command and the points of the valid prefix are returned.
 pub fn points_on_path<F>(
     path: String,
     tolerance: Option<F>,
@@ -17,8 +22,32 @@ where
     F: RealNumber + Display,
 {
     let path_parser = PathParser::from(path.as_ref());
-    let path_segments: Vec<PathSegment> = path_parser.flatten().collect();
-    let normalized_segments = normalize(absolutize(path_segments.iter()));
+    points_on_segments(path_parser.flatten(), tolerance, distance).unwrap_or_default()
+}
+
+/// Like [`points_on_path`], but fails on malformed path data.
+pub fn try_points_on_path<F>(
+    path: &str,
+    tolerance: Option<F>,
+    distance: Option<F>,
+) -> Result<Vec<Vec<Point2<F>>>, Error>
+where
+    F: RealNumber + Display,
+{
+    points_on_segments(parse_path_segments(path)?, tolerance, distance)
+}
+
+/// Samples points along a sequence of path segments, which may be relative
+/// and use any SVG command.
+pub fn points_on_segments<F>(
+    path_segments: impl IntoIterator<Item = impl Borrow<PathSegment>>,
+    tolerance: Option<F>,
+    distance: Option<F>,
+) -> Result<Vec<Vec<Point2<F>>>, Error>
+where
+    F: RealNumber + Display,
+{
+    let normalized_segments = normalize(absolutize(path_segments.into_iter()));
     // normalized_segments
     //     .by_ref()
     //     .for_each(|a| print_line_segment(&a));
@@ -54,12 +83,12 @@ where
         match segment {
             PathSegment::MoveTo { abs: true, x, y } => {
                 append_pending_points(&mut current_points, &mut pending_curve);
-                start = Point2::new(_cc::<F>(x), _cc::<F>(y));
+                start = Point2::new(_try_cc::<F>(x)?, _try_cc::<F>(y)?);
                 current_points.push(start);
             }
             PathSegment::LineTo { abs: true, x, y } => {
                 append_pending_curve(&mut current_points, &mut pending_curve);
-                current_points.push(Point2::new(_cc::<F>(x), _cc::<F>(y)));
+                current_points.push(Point2::new(_try_cc::<F>(x)?, _try_cc::<F>(y)?));
             }
             PathSegment::CurveTo {
                 abs: true,
@@ -78,15 +107,15 @@ where
                     };
                     pending_curve.push(*last_point);
                 }
-                pending_curve.push(Point2::new(_cc::<F>(x1), _cc::<F>(y1)));
-                pending_curve.push(Point2::new(_cc::<F>(x2), _cc::<F>(y2)));
-                pending_curve.push(Point2::new(_cc::<F>(x), _cc::<F>(y)));
+                pending_curve.push(Point2::new(_try_cc::<F>(x1)?, _try_cc::<F>(y1)?));
+                pending_curve.push(Point2::new(_try_cc::<F>(x2)?, _try_cc::<F>(y2)?));
+                pending_curve.push(Point2::new(_try_cc::<F>(x)?, _try_cc::<F>(y)?));
             }
             PathSegment::ClosePath { abs: true } => {
                 append_pending_curve(&mut current_points, &mut pending_curve);
                 current_points.push(start);
             }
-            _ => panic!("unexpected  path segment"),
+            segment => return Err(Error::UnexpectedSegment(segment)),
         }
     }
 
@@ -100,8 +129,8 @@ where
                 out.push(simplified_set);
             }
         }
-        out
+        Ok(out)
     } else {
-        sets
+        Ok(sets)
     }
 }
@@ -0,0 +1,128 @@
+#[cfg(feature = "png")]
+use std::path::Path;
+
+use crate::error::Error;
+
+/// A grayscale raster, stored row by row from the top left. Each pixel is a
+/// luminance from 0 for black to 1 for white.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GrayImage {
+    width: usize,
+    height: usize,
+    pixels: Vec<f32>,
+}
+
+impl GrayImage {
+    pub fn new(width: usize, height: usize, pixels: Vec<f32>) -> Result<Self, Error> {
+        if width == 0 || height == 0 {
+            return Err(Error::InvalidImage(format!(
+                "image of {}x{} pixels is empty",
+                width, height
+            )));
+        }
+        if pixels.len() != width * height {
+            return Err(Error::InvalidImage(format!(
+                "expected {} pixels for {}x{}, got {}",
+                width * height,
+                width,
+                height,
+                pixels.len()
+            )));
+        }
+        Ok(GrayImage {
+            width,
+            height,
+            pixels: pixels.into_iter().map(|p| p.clamp(0.0, 1.0)).collect(),
+        })
+    }
+
+    /// Builds an image from 8 bit luminance values.
+    pub fn from_luma8(width: usize, height: usize, pixels: &[u8]) -> Result<Self, Error> {
+        Self::new(
+            width,
+            height,
+            pixels.iter().map(|p| f32::from(*p) / 255.0).collect(),
+        )
+    }
+
+    /// Decodes a PNG of any colour type. Colours are reduced to their
+    /// luminance and transparent pixels are blended over white.
+    #[cfg(feature = "png")]
+    pub fn from_png_bytes(data: &[u8]) -> Result<Self, Error> {
+        let invalid = |e: png::DecodingError| Error::InvalidImage(e.to_string());
+        let mut decoder = png::Decoder::new(data);
+        decoder.set_transformations(png::Transformations::normalize_to_color8());
+        let mut reader = decoder.read_info().map_err(invalid)?;
+        let mut buffer = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut buffer).map_err(invalid)?;
+        let channels = info.color_type.samples();
+        let pixels = buffer[..info.buffer_size()]
+            .chunks_exact(channels)
+            .map(|pixel| {
+                let sample = |i: usize| f32::from(pixel[i]) / 255.0;
+                let (luminance, alpha) = match channels {
+                    1 => (sample(0), 1.0),
+                    2 => (sample(0), sample(1)),
+                    // Rec. 709 luma weights
+                    _ => (
+                        0.2126 * sample(0) + 0.7152 * sample(1) + 0.0722 * sample(2),
+                        if channels == 4 { sample(3) } else { 1.0 },
+                    ),
+                };
+                luminance * alpha + 1.0 - alpha
+            })
+            .collect();
+        Self::new(info.width as usize, info.height as usize, pixels)
+    }
+
+    #[cfg(feature = "png")]
+    pub fn from_png_file(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let data = std::fs::read(path)
+            .map_err(|e| Error::InvalidImage(format!("{}: {}", path.display(), e)))?;
+        Self::from_png_bytes(&data)
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// The luminance at `(u, v)`, where `(0, 0)` is the top left corner of the
+    /// image and `(1, 1)` the bottom right. Pixels are interpolated bilinearly
+    /// and positions outside the image take the nearest edge pixel.
+    pub fn luminance(&self, u: f64, v: f64) -> f64 {
+        let x = (u * self.width as f64 - 0.5).clamp(0.0, (self.width - 1) as f64);
+        let y = (v * self.height as f64 - 0.5).clamp(0.0, (self.height - 1) as f64);
+        let (x0, y0) = (x.floor() as usize, y.floor() as usize);
+        let (x1, y1) = ((x0 + 1).min(self.width - 1), (y0 + 1).min(self.height - 1));
+        let (fx, fy) = (x - x0 as f64, y - y0 as f64);
+        let pixel = |x: usize, y: usize| f64::from(self.pixels[y * self.width + x]);
+        let top = pixel(x0, y0) * (1.0 - fx) + pixel(x1, y0) * fx;
+        let bottom = pixel(x0, y1) * (1.0 - fx) + pixel(x1, y1) * fx;
+        top * (1.0 - fy) + bottom * fy
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::relative_eq;
+
+    use super::GrayImage;
+    use crate::error::Error;
+
+    #[test]
+    fn luminance_is_interpolated_between_pixels() {
+        let image = GrayImage::from_luma8(2, 1, &[0, 255]).unwrap();
+        assert!(relative_eq!(image.luminance(0.0, 0.5), 0.0));
+        assert!(relative_eq!(image.luminance(0.5, 0.5), 0.5));
+        assert!(relative_eq!(image.luminance(2.0, 0.5), 1.0));
+        assert!(matches!(
+            GrayImage::new(2, 2, vec![0.0; 3]),
+            Err(Error::InvalidImage(_))
+        ));
+    }
+}
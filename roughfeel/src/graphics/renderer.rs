@@ -1,5 +1,5 @@
 // Copy from https://github.com/orhanbalci/rough-rs/blob/main/roughr/src/renderer.rs
-use std::borrow::BorrowMut;
+use std::borrow::{Borrow, BorrowMut};
 
 use nalgebra::Point2;
 use nalgebra_glm::RealNumber;
@@ -7,15 +7,18 @@ use svg_path_ops::{absolutize, normalize};
 use svgtypes::{PathParser, PathSegment};
 
 use super::drawable::DrawOptions;
-use super::{_c, _cc};
+use super::{_c, _try_cc};
+use crate::error::Error;
 use crate::graphics::drawable_ops::{Op, OpSet, OpSetType, OpType};
 use crate::graphics::filler::get_filler;
 use crate::graphics::filler::FillerType::{
-    DashedFiller, DotFiller, HatchFiller, ScanLineHachure, ZigZagFiller, ZigZagLineFiller,
+    ContourFiller, DashedFiller, DotFiller, HatchFiller, ScanLineHachure, ScribbleFiller,
+    SpiralFiller, StippleFiller, ZigZagFiller, ZigZagLineFiller,
 };
 use crate::graphics::geometry::{convert_bezier_quadratic_to_cubic, BezierQuadratic};
 use crate::graphics::get_pi;
 use crate::graphics::paint::FillStyle;
+use crate::graphics::path_data::parse_path_segments;
 
 #[derive(PartialEq, Eq, Debug)]
 pub struct EllipseParams<F: RealNumber> {
@@ -252,6 +255,166 @@ pub fn rectangle<F: RealNumber>(x: F, y: F, width: F, height: F, o: &mut DrawOpt
     polygon(&points, o)
 }
 
+/// Clamps corner radii, ordered top-left, top-right, bottom-right and
+/// bottom-left, so that the corners on each side do not overlap. Like CSS
+/// `border-radius`, all radii are scaled down by the same factor.
+pub fn clamp_corner_radii<F: RealNumber>(width: F, height: F, radii: [F; 4]) -> [F; 4] {
+    let radii = radii.map(|r| r.max(F::zero()));
+    let sides = [
+        (width.abs(), radii[0] + radii[1]),
+        (height.abs(), radii[1] + radii[2]),
+        (width.abs(), radii[2] + radii[3]),
+        (height.abs(), radii[3] + radii[0]),
+    ];
+    let scale = sides
+        .iter()
+        .filter(|(_, sum)| *sum > F::zero())
+        .fold(F::one(), |scale, (length, sum)| scale.min(*length / *sum));
+    radii.map(|r| r * scale)
+}
+
+/// Centers and angle ranges of the four corner arcs, clockwise from the top-left.
+fn corner_arcs<F: RealNumber>(
+    x: F,
+    y: F,
+    width: F,
+    height: F,
+    radii: [F; 4],
+) -> [(F, F, F, F, F); 4] {
+    let (left, right) = (x.min(x + width), x.max(x + width));
+    let (top, bottom) = (y.min(y + height), y.max(y + height));
+    let pi = get_pi::<F>();
+    let half_pi = pi / _c(2.0);
+    [
+        (left + radii[0], top + radii[0], radii[0], pi, pi + half_pi),
+        (
+            right - radii[1],
+            top + radii[1],
+            radii[1],
+            pi + half_pi,
+            pi * _c(2.0),
+        ),
+        (
+            right - radii[2],
+            bottom - radii[2],
+            radii[2],
+            F::zero(),
+            half_pi,
+        ),
+        (left + radii[3], bottom - radii[3], radii[3], half_pi, pi),
+    ]
+}
+
+/// Sketches a rectangle with rounded corners. `radii` are ordered top-left,
+/// top-right, bottom-right and bottom-left and are clamped with
+/// [`clamp_corner_radii`]. Straight edges are double lines and corners are
+/// open arcs.
+pub fn rounded_rectangle<F: RealNumber>(
+    x: F,
+    y: F,
+    width: F,
+    height: F,
+    radii: [F; 4],
+    o: &mut DrawOptions,
+) -> OpSet<F> {
+    let radii = clamp_corner_radii(width, height, radii);
+    let corners = corner_arcs(x, y, width, height, radii);
+    let mut ops = vec![];
+    for (i, &(cx, cy, r, start, stop)) in corners.iter().enumerate() {
+        if r > F::zero() {
+            let mut corner = arc(
+                cx,
+                cy,
+                r * _c(2.0),
+                r * _c(2.0),
+                start,
+                stop,
+                false,
+                false,
+                o,
+            );
+            ops.append(&mut corner.ops);
+        }
+        let (ncx, ncy, nr, nstart, _) = corners[(i + 1) % 4];
+        let (x1, y1) = (cx + r * stop.cos(), cy + r * stop.sin());
+        let (x2, y2) = (ncx + nr * nstart.cos(), ncy + nr * nstart.sin());
+        if (x2 - x1).abs() + (y2 - y1).abs() > F::zero() {
+            ops.append(&mut _double_line(x1, y1, x2, y2, o, false));
+        }
+    }
+    OpSet {
+        op_set_type: OpSetType::Path,
+        ops,
+        path: None,
+        size: None,
+    }
+}
+
+/// The exact outline of a rounded rectangle, with each corner sampled at
+/// `corner_steps` intervals. Used as the fill polygon.
+pub fn rounded_rectangle_points<F: RealNumber>(
+    x: F,
+    y: F,
+    width: F,
+    height: F,
+    radii: [F; 4],
+    corner_steps: usize,
+) -> Vec<Point2<F>> {
+    let radii = clamp_corner_radii(width, height, radii);
+    let mut points = vec![];
+    for (cx, cy, r, start, stop) in corner_arcs(x, y, width, height, radii) {
+        let steps = if r > F::zero() {
+            corner_steps.max(1)
+        } else {
+            0
+        };
+        for i in 0..=steps {
+            let angle = start + (stop - start) * _c::<F>(i as f32) / _c::<F>(steps.max(1) as f32);
+            points.push(Point2::new(cx + r * angle.cos(), cy + r * angle.sin()));
+        }
+    }
+    points
+}
+
+/// Vertices of a regular polygon inscribed in a circle of radius `r`. With
+/// zero `rotation` (in radians) the first vertex points up.
+pub fn regular_polygon_points<F: RealNumber>(
+    cx: F,
+    cy: F,
+    r: F,
+    sides: usize,
+    rotation: F,
+) -> Vec<Point2<F>> {
+    let step = get_pi::<F>() * _c(2.0) / _c::<F>(sides.max(1) as f32);
+    let start = rotation - get_pi::<F>() / _c(2.0);
+    (0..sides)
+        .map(|i| {
+            let angle = start + step * _c::<F>(i as f32);
+            Point2::new(cx + r * angle.cos(), cy + r * angle.sin())
+        })
+        .collect()
+}
+
+/// Vertices of a star with `points` tips on a circle of radius `outer_r`,
+/// alternating with inner corners at `inner_r`. The first tip points up.
+pub fn star_points<F: RealNumber>(
+    cx: F,
+    cy: F,
+    outer_r: F,
+    inner_r: F,
+    points: usize,
+) -> Vec<Point2<F>> {
+    let step = get_pi::<F>() / _c::<F>(points.max(1) as f32);
+    let start = -get_pi::<F>() / _c(2.0);
+    (0..points * 2)
+        .map(|i| {
+            let angle = start + step * _c::<F>(i as f32);
+            let r = if i % 2 == 0 { outer_r } else { inner_r };
+            Point2::new(cx + r * angle.cos(), cy + r * angle.sin())
+        })
+        .collect()
+}
+
 pub fn bezier_quadratic<F: RealNumber>(
     start: Point2<F>,
     cp: Point2<F>,
@@ -339,12 +502,26 @@ pub fn ellipse_with_params<F: RealNumber>(
     o: &mut DrawOptions,
     ellipse_params: &EllipseParams<F>,
 ) -> EllipseResult<F> {
-    let ellipse_points = _compute_ellipse_points(
+    superellipse_with_params(x, y, _c(2.0), o, ellipse_params)
+}
+
+/// Sketches a superellipse `|x / rx|^n + |y / ry|^n = 1` centered on `x`, `y`
+/// with the ellipse curve fitting. An `exponent` of 2 gives an ellipse, larger
+/// values approach a rectangle and values below 1 give a pinched star.
+pub fn superellipse_with_params<F: RealNumber>(
+    x: F,
+    y: F,
+    exponent: F,
+    o: &mut DrawOptions,
+    ellipse_params: &EllipseParams<F>,
+) -> EllipseResult<F> {
+    let ellipse_points = _compute_superellipse_points(
         ellipse_params.increment,
         x,
         y,
         ellipse_params.rx,
         ellipse_params.ry,
+        exponent,
         _c(1.0),
         ellipse_params.increment
             * _offset(
@@ -359,12 +536,13 @@ pub fn ellipse_with_params<F: RealNumber>(
     let cp1 = ellipse_points[1].clone();
     let mut o1 = _curve(&ap1, None, o);
     if (!o.disable_multi_stroke.unwrap_or(false)) && (o.roughness.unwrap_or(0.0) != 0.0) {
-        let inner_ellipse_points = _compute_ellipse_points(
+        let inner_ellipse_points = _compute_superellipse_points(
             ellipse_params.increment,
             x,
             y,
             ellipse_params.rx,
             ellipse_params.ry,
+            exponent,
             _c::<F>(1.5),
             _c::<F>(0.0),
             o,
@@ -745,11 +923,14 @@ pub(crate) fn _curve<F: RealNumber>(
     ops
 }
 
-fn _curve_with_offset<F: RealNumber>(
+pub(crate) fn _curve_with_offset<F: RealNumber>(
     points: &[Point2<F>],
     offset: F,
     o: &mut DrawOptions,
 ) -> Vec<Op<F>> {
+    if points.is_empty() {
+        return vec![];
+    }
     let mut ps: Vec<Point2<F>> = vec![
         Point2::new(
             points[0].x + _offset_opt(offset, o, None),
@@ -786,6 +967,23 @@ pub(crate) fn _compute_ellipse_points<F: RealNumber>(
     offset: F,
     overlap: F,
     o: &mut DrawOptions,
+) -> Vec<Vec<Point2<F>>> {
+    _compute_superellipse_points(increment, cx, cy, rx, ry, _c(2.0), offset, overlap, o)
+}
+
+/// Samples a superellipse `|x / rx|^n + |y / ry|^n = 1` the same way as an
+/// ellipse, which is the superellipse with `exponent` 2.
+#[allow(clippy::too_many_arguments)]
+fn _compute_superellipse_points<F: RealNumber>(
+    increment: F,
+    cx: F,
+    cy: F,
+    rx: F,
+    ry: F,
+    exponent: F,
+    offset: F,
+    overlap: F,
+    o: &mut DrawOptions,
 ) -> Vec<Vec<Point2<F>>> {
     let core_only = o.roughness.unwrap_or(0.0) == 0.0;
     let mut core_points: Vec<Point2<F>> = Vec::new();
@@ -795,38 +993,45 @@ pub(crate) fn _compute_ellipse_points<F: RealNumber>(
         // Smooth edge
         let increment_inner = increment / _c(4.0);
         all_points.push(Point2::new(
-            cx + rx * (-increment_inner).cos(),
-            cy + ry * (-increment_inner).sin(),
+            cx + rx * _super_cos(-increment_inner, exponent),
+            cy + ry * _super_sin(-increment_inner, exponent),
         ));
 
         let mut angle = _c::<F>(0.0);
         while angle <= _c(get_pi::<f32>() * 2.0) {
-            let p = Point2::new(cx + rx * angle.cos(), cy + ry * angle.sin());
+            let p = Point2::new(
+                cx + rx * _super_cos(angle, exponent),
+                cy + ry * _super_sin(angle, exponent),
+            );
             core_points.push(p);
             all_points.push(p);
             angle = angle + increment_inner;
         }
         all_points.push(Point2::new(
-            cx + rx * (_c::<F>(0.0)).cos(),
-            cy + ry * (_c::<F>(0.0)).sin(),
+            cx + rx * _super_cos(_c::<F>(0.0), exponent),
+            cy + ry * _super_sin(_c::<F>(0.0), exponent),
         ));
         all_points.push(Point2::new(
-            cx + rx * increment_inner.cos(),
-            cy + ry * increment_inner.sin(),
+            cx + rx * _super_cos(increment_inner, exponent),
+            cy + ry * _super_sin(increment_inner, exponent),
         ));
     } else {
         // Rough edge
         let rad_offset: F = _offset_opt::<F>(_c(0.5), o, None) - (_c::<F>(get_pi()) / _c(2.0));
         all_points.push(Point2::new(
-            _offset_opt(offset, o, None) + cx + _c::<F>(0.9) * rx * (rad_offset - increment).cos(),
-            _offset_opt(offset, o, None) + cy + _c::<F>(0.9) * ry * (rad_offset - increment).sin(),
+            _offset_opt(offset, o, None)
+                + cx
+                + _c::<F>(0.9) * rx * _super_cos(rad_offset - increment, exponent),
+            _offset_opt(offset, o, None)
+                + cy
+                + _c::<F>(0.9) * ry * _super_sin(rad_offset - increment, exponent),
         ));
         let end_angle = _c::<F>(get_pi()) * _c(2.0) + rad_offset - _c(0.01);
         let mut angle = rad_offset;
         while angle < end_angle {
             let p = Point2::new(
-                _offset_opt(offset, o, None) + cx + rx * angle.cos(),
-                _offset_opt(offset, o, None) + cy + ry * angle.sin(),
+                _offset_opt(offset, o, None) + cx + rx * _super_cos(angle, exponent),
+                _offset_opt(offset, o, None) + cy + ry * _super_sin(angle, exponent),
             );
             core_points.push(p);
             all_points.push(p);
@@ -836,27 +1041,54 @@ pub(crate) fn _compute_ellipse_points<F: RealNumber>(
         all_points.push(Point2::new(
             _offset_opt(offset, o, None)
                 + cx
-                + rx * (rad_offset + _c::<F>(get_pi()) * _c(2.0) + overlap * _c(0.5)).cos(),
+                + rx * _super_cos(
+                    rad_offset + _c::<F>(get_pi()) * _c(2.0) + overlap * _c(0.5),
+                    exponent,
+                ),
             _offset_opt(offset, o, None)
                 + cy
-                + ry * (rad_offset + _c::<F>(get_pi()) * _c(2.0) + overlap * _c(0.5)).sin(),
+                + ry * _super_sin(
+                    rad_offset + _c::<F>(get_pi()) * _c(2.0) + overlap * _c(0.5),
+                    exponent,
+                ),
         ));
         all_points.push(Point2::new(
-            _offset_opt(offset, o, None) + cx + _c::<F>(0.98) * rx * (rad_offset + overlap).cos(),
-            _offset_opt(offset, o, None) + cy + _c::<F>(0.98) * ry * (rad_offset + overlap).sin(),
+            _offset_opt(offset, o, None)
+                + cx
+                + _c::<F>(0.98) * rx * _super_cos(rad_offset + overlap, exponent),
+            _offset_opt(offset, o, None)
+                + cy
+                + _c::<F>(0.98) * ry * _super_sin(rad_offset + overlap, exponent),
         ));
         all_points.push(Point2::new(
             _offset_opt(offset, o, None)
                 + cx
-                + _c::<F>(0.9) * rx * (rad_offset + overlap * _c(0.5)).cos(),
+                + _c::<F>(0.9) * rx * _super_cos(rad_offset + overlap * _c(0.5), exponent),
             _offset_opt(offset, o, None)
                 + cy
-                + _c::<F>(0.9) * ry * (rad_offset + overlap * _c(0.5)).sin(),
+                + _c::<F>(0.9) * ry * _super_sin(rad_offset + overlap * _c(0.5), exponent),
         ));
     }
     vec![all_points, core_points]
 }
 
+/// `cos` raised to `2 / exponent` keeping its sign, exact for ellipses.
+fn _super_cos<F: RealNumber>(angle: F, exponent: F) -> F {
+    _super_power(angle.cos(), exponent)
+}
+
+fn _super_sin<F: RealNumber>(angle: F, exponent: F) -> F {
+    _super_power(angle.sin(), exponent)
+}
+
+fn _super_power<F: RealNumber>(value: F, exponent: F) -> F {
+    if exponent == _c(2.0) {
+        value
+    } else {
+        value.signum() * value.abs().powf(_c::<F>(2.0) / exponent)
+    }
+}
+
 fn _arc<F: RealNumber>(
     increment: F,
     cx: F,
@@ -999,6 +1231,10 @@ where
             FillStyle::CrossHatch => get_filler(HatchFiller),
             FillStyle::ZigZag => get_filler(ZigZagFiller),
             FillStyle::ZigZagLine => get_filler(ZigZagLineFiller),
+            FillStyle::Contour => get_filler(ContourFiller),
+            FillStyle::Spiral => get_filler(SpiralFiller),
+            FillStyle::Scribble => get_filler(ScribbleFiller),
+            FillStyle::Stipple => get_filler(StippleFiller),
             _ => get_filler(ScanLineHachure),
         }
     } else {
@@ -1016,6 +1252,23 @@ pub fn pattern_fill_arc<F>(
     stop: F,
     o: &mut DrawOptions,
 ) -> OpSet<F>
+where
+    F: RealNumber,
+{
+    let points = arc_fill_points(x, y, width, height, start, stop, o);
+    pattern_fill_polygons(vec![points], o)
+}
+
+/// The jittered pie slice that `pattern_fill_arc` fills.
+pub fn arc_fill_points<F>(
+    x: F,
+    y: F,
+    width: F,
+    height: F,
+    start: F,
+    stop: F,
+    o: &mut DrawOptions,
+) -> Vec<Point2<F>>
 where
     F: RealNumber,
 {
@@ -1053,19 +1306,66 @@ where
 
     points.push(Point2::new(cx + rx * stp.cos(), cy + ry * stp.sin()));
     points.push(Point2::new(cx, cy));
-    pattern_fill_polygons(vec![points], o)
+    points
 }
 
+/// Draws an SVG path. Parsing stops at the first malformed command and the
+/// valid prefix of the path is drawn.
 pub fn svg_path<F>(path: String, o: &mut DrawOptions) -> OpSet<F>
+where
+    F: RealNumber,
+{
+    let path_parser = PathParser::from(path.as_ref());
+    let mut ops = vec![];
+    // a segment that cannot be drawn ends the path like malformed data does
+    let _ = push_svg_path_segments(path_parser.flatten(), o, &mut ops);
+    OpSet {
+        op_set_type: OpSetType::Path,
+        ops,
+        size: None,
+        path: None,
+    }
+}
+
+/// Like [`svg_path`], but fails on malformed path data.
+pub fn try_svg_path<F>(path: &str, o: &mut DrawOptions) -> Result<OpSet<F>, Error>
+where
+    F: RealNumber,
+{
+    svg_path_segments(parse_path_segments(path)?, o)
+}
+
+/// Draws a sequence of path segments, which may be relative and use any SVG command.
+pub fn svg_path_segments<F>(
+    path_segments: impl IntoIterator<Item = impl Borrow<PathSegment>>,
+    o: &mut DrawOptions,
+) -> Result<OpSet<F>, Error>
 where
     F: RealNumber,
 {
     let mut ops = vec![];
+    push_svg_path_segments(path_segments, o, &mut ops)?;
+    Ok(OpSet {
+        op_set_type: OpSetType::Path,
+        ops,
+        size: None,
+        path: None,
+    })
+}
+
+/// Appends the ops of `path_segments` to `ops`, stopping at the first segment
+/// that fails.
+fn push_svg_path_segments<F>(
+    path_segments: impl IntoIterator<Item = impl Borrow<PathSegment>>,
+    o: &mut DrawOptions,
+    ops: &mut Vec<Op<F>>,
+) -> Result<(), Error>
+where
+    F: RealNumber,
+{
     let mut first = Point2::new(_c::<F>(0.0), _c::<F>(0.0));
     let mut current = Point2::new(_c::<F>(0.0), _c::<F>(0.0));
-    let path_parser = PathParser::from(path.as_ref());
-    let path_segments: Vec<PathSegment> = path_parser.flatten().collect();
-    let mut normalized_segments = normalize(absolutize(path_segments.iter()));
+    let normalized_segments = normalize(absolutize(path_segments.into_iter()));
     // normalized_segments
     //     .by_ref()
     //     .for_each(|s| print_line_segment(&s));
@@ -1078,30 +1378,30 @@ where
                     op: OpType::Move,
                     data: vec![
                         if pv {
-                            _cc::<F>(x)
+                            _try_cc::<F>(x)?
                         } else {
-                            _cc::<F>(x) + _offset_opt(ro, o, None)
+                            _try_cc::<F>(x)? + _offset_opt(ro, o, None)
                         },
                         if pv {
-                            _cc::<F>(y)
+                            _try_cc::<F>(y)?
                         } else {
-                            _cc::<F>(y) + _offset_opt(ro, o, None)
+                            _try_cc::<F>(y)? + _offset_opt(ro, o, None)
                         },
                     ],
                 });
-                current = Point2::new(_cc::<F>(x), _cc::<F>(y));
-                first = Point2::new(_cc::<F>(x), _cc::<F>(y));
+                current = Point2::new(_try_cc::<F>(x)?, _try_cc::<F>(y)?);
+                first = Point2::new(_try_cc::<F>(x)?, _try_cc::<F>(y)?);
             }
             PathSegment::LineTo { abs: true, x, y } => {
                 ops.extend(_double_line(
                     current.x,
                     current.y,
-                    _cc::<F>(x),
-                    _cc::<F>(y),
+                    _try_cc::<F>(x)?,
+                    _try_cc::<F>(y)?,
                     o,
                     false,
                 ));
-                current = Point2::new(_cc::<F>(x), _cc::<F>(y));
+                current = Point2::new(_try_cc::<F>(x)?, _try_cc::<F>(y)?);
             }
             PathSegment::CurveTo {
                 abs: true,
@@ -1113,16 +1413,16 @@ where
                 y,
             } => {
                 ops.extend(_bezier_to(
-                    _cc::<F>(x1),
-                    _cc::<F>(y1),
-                    _cc::<F>(x2),
-                    _cc::<F>(y2),
-                    _cc::<F>(x),
-                    _cc::<F>(y),
+                    _try_cc::<F>(x1)?,
+                    _try_cc::<F>(y1)?,
+                    _try_cc::<F>(x2)?,
+                    _try_cc::<F>(y2)?,
+                    _try_cc::<F>(x)?,
+                    _try_cc::<F>(y)?,
                     &current,
                     o,
                 ));
-                current = Point2::new(_cc::<F>(x), _cc::<F>(y));
+                current = Point2::new(_try_cc::<F>(x)?, _try_cc::<F>(y)?);
             }
             PathSegment::ClosePath { abs: true } => {
                 ops.extend(_double_line(
@@ -1130,27 +1430,23 @@ where
                 ));
                 current = Point2::new(first.x, first.y);
             }
-            _ => panic!("Unexpected segment type"),
+            segment => return Err(Error::UnexpectedSegment(segment)),
         }
     }
-    OpSet {
-        op_set_type: OpSetType::Path,
-        ops,
-        size: None,
-        path: None,
-    }
+    Ok(())
 }
 
 #[cfg(test)]
 mod test {
     // use euclid::point2;
+    use approx::relative_eq;
     use nalgebra::Point2;
     use plotlib::page::Page;
     use plotlib::repr::Plot;
     use plotlib::style::{PointMarker, PointStyle};
     use plotlib::view::ContinuousView;
 
-    use super::{EllipseParams, _compute_ellipse_points, _curve};
+    use super::{_compute_ellipse_points, _curve, EllipseParams};
     use crate::graphics::drawable::{DrawOptions, DrawOptionsBuilder};
     use crate::graphics::drawable_ops::{Op, OpSet, OpSetType, OpType};
 
@@ -1443,6 +1739,98 @@ mod test {
         );
     }
 
+    #[test]
+    fn corner_radii_are_clamped() {
+        assert_eq!(
+            super::clamp_corner_radii(100.0, 40.0, [10.0, -5.0, 30.0, 70.0]),
+            [5.0, 0.0, 15.0, 35.0]
+        );
+        assert_eq!(
+            super::clamp_corner_radii(100.0, 100.0, [10.0, 20.0, 30.0, 40.0]),
+            [10.0, 20.0, 30.0, 40.0]
+        );
+    }
+
+    #[test]
+    fn rounded_rectangle_points_follow_corners() {
+        let points =
+            super::rounded_rectangle_points(0.0, 0.0, 100.0, 50.0, [20.0, 0.0, 10.0, 0.0], 4);
+        assert_eq!(points.len(), 5 + 1 + 5 + 1);
+        assert!(relative_eq!(
+            points[0],
+            Point2::new(0.0, 20.0),
+            epsilon = 1.0e-9
+        ));
+        assert!(relative_eq!(
+            points[5],
+            Point2::new(100.0, 0.0),
+            epsilon = 1.0e-9
+        ));
+        let center = Point2::new(20.0, 20.0);
+        assert!(points[..5]
+            .iter()
+            .all(|p: &Point2<f64>| (nalgebra::distance(p, &center) - 20.0).abs() < 1.0e-9));
+        let result = super::rounded_rectangle(
+            0.0,
+            0.0,
+            100.0,
+            50.0,
+            [20.0, 0.0, 10.0, 0.0],
+            &mut get_default_options(),
+        );
+        assert_eq!(result.op_set_type, OpSetType::Path);
+        assert_eq!(result.ops[0].op, OpType::Move);
+    }
+
+    #[test]
+    fn regular_polygon_and_star_vertices() {
+        let hexagon = super::regular_polygon_points(10.0, 20.0, 5.0, 6, 0.0);
+        assert_eq!(hexagon.len(), 6);
+        assert!(relative_eq!(
+            hexagon[0],
+            Point2::new(10.0, 15.0),
+            epsilon = 1.0e-9
+        ));
+        let star = super::star_points(0.0, 0.0, 10.0, 4.0, 5);
+        assert_eq!(star.len(), 10);
+        for (i, p) in star.iter().enumerate() {
+            let r = if i % 2 == 0 { 10.0 } else { 4.0 };
+            assert!(relative_eq!(p.coords.norm(), r, epsilon = 1.0e-9));
+        }
+    }
+
+    #[test]
+    fn superellipse_points_lie_on_curve() {
+        let mut o = DrawOptionsBuilder::default()
+            .roughness(0.0)
+            .build()
+            .unwrap();
+        let params = EllipseParams {
+            increment: 0.3,
+            rx: 20.0,
+            ry: 10.0,
+        };
+        let result = super::superellipse_with_params(5.0, 5.0, 4.0, &mut o, &params);
+        assert!(!result.estimated_points.is_empty());
+        for p in result.estimated_points.iter() {
+            let value =
+                ((p.x - 5.0) / 20.0f64).abs().powi(4) + ((p.y - 5.0) / 10.0f64).abs().powi(4);
+            assert!(relative_eq!(value, 1.0, epsilon = 1.0e-9));
+        }
+        let ellipse = super::ellipse_with_params(5.0, 5.0, &mut o.clone(), &params);
+        let superellipse = super::superellipse_with_params(5.0, 5.0, 2.0, &mut o, &params);
+        assert_eq!(ellipse.opset, superellipse.opset);
+    }
+
+    #[test]
+    fn svg_path_draws_the_prefix_before_a_failing_segment() {
+        let mut o = get_default_options();
+        let prefix: OpSet<f32> = super::svg_path("M 0 0 L 10 0".into(), &mut o.clone());
+        let failing: OpSet<f32> = super::svg_path("M 0 0 L 10 0 L 1e39 0".into(), &mut o);
+        assert!(!failing.ops.is_empty());
+        assert_eq!(failing.ops, prefix.ops);
+    }
+
     #[test]
     #[ignore = "utility to see results quickly"]
     fn plot_points() {
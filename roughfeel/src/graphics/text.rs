@@ -0,0 +1,235 @@
+use std::path::Path;
+
+use derive_builder::Builder;
+use nalgebra_glm::RealNumber;
+use ttf_parser::{Face, GlyphId, OutlineBuilder};
+
+use super::_cc;
+use super::path_builder::RoughPathBuilder;
+use crate::error::Error;
+
+/// A TrueType or OpenType font used to sketch text.
+#[derive(Clone, Debug)]
+pub struct Font {
+    data: Vec<u8>,
+    index: u32,
+}
+
+impl Font {
+    /// Loads the first face of a font file or collection.
+    pub fn from_bytes(data: Vec<u8>) -> Result<Self, Error> {
+        Self::from_bytes_with_index(data, 0)
+    }
+
+    /// Loads face `index` of a font collection.
+    pub fn from_bytes_with_index(data: Vec<u8>, index: u32) -> Result<Self, Error> {
+        Face::parse(&data, index).map_err(|e| Error::InvalidFont(e.to_string()))?;
+        Ok(Font { data, index })
+    }
+
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let data = std::fs::read(path)
+            .map_err(|e| Error::InvalidFont(format!("{}: {}", path.display(), e)))?;
+        Self::from_bytes(data)
+    }
+
+    fn face(&self) -> Face<'_> {
+        Face::parse(&self.data, self.index).expect("font was validated when loaded")
+    }
+}
+
+#[derive(Clone, Debug, Builder)]
+#[builder(setter(strip_option))]
+pub struct TextOptions {
+    /// Font size in pixels, the height of the em square.
+    #[builder(default = "16.0")]
+    pub size: f32,
+    /// Distance between baselines as a multiple of the font's own line height.
+    #[builder(default = "1.0")]
+    pub line_height: f32,
+    /// Extra space added after each glyph, in pixels.
+    #[builder(default = "0.0")]
+    pub letter_spacing: f32,
+    /// Whether to apply the pair adjustments of the font's `kern` table.
+    #[builder(default = "true")]
+    pub kerning: bool,
+}
+
+impl Default for TextOptions {
+    fn default() -> Self {
+        TextOptionsBuilder::default()
+            .build()
+            .expect("failed to build default text options")
+    }
+}
+
+/// Lays out `text` and collects the glyph outlines into a path. `(x, y)` is
+/// the left end of the first baseline and lines break at `\n`. Glyph contours
+/// become subpaths, so counters such as the hole in "o" are left unfilled.
+pub fn text_path<F: RealNumber>(
+    font: &Font,
+    text: &str,
+    x: F,
+    y: F,
+    options: &TextOptions,
+) -> RoughPathBuilder<F> {
+    let face = font.face();
+    let scale = f64::from(options.size) / f64::from(face.units_per_em());
+    let line_advance = f64::from(options.line_height)
+        * scale
+        * (f64::from(face.ascender()) - f64::from(face.descender()) + f64::from(face.line_gap()));
+    let mut builder = RoughPathBuilder::new();
+    for (line, content) in text.lines().enumerate() {
+        let mut pen = GlyphPen {
+            builder: &mut builder,
+            scale,
+            x,
+            y: y + _cc::<F>(line as f64 * line_advance),
+        };
+        let mut advance = 0.0;
+        let mut previous: Option<GlyphId> = None;
+        for c in content.chars() {
+            let glyph = face.glyph_index(c).unwrap_or(GlyphId(0));
+            if let (true, Some(left)) = (options.kerning, previous) {
+                advance += scale * f64::from(kerning(&face, left, glyph));
+            }
+            pen.x = x + _cc::<F>(advance);
+            face.outline_glyph(glyph, &mut pen);
+            advance += scale * f64::from(face.glyph_hor_advance(glyph).unwrap_or(0))
+                + f64::from(options.letter_spacing);
+            previous = Some(glyph);
+        }
+    }
+    builder
+}
+
+fn kerning(face: &Face, left: GlyphId, right: GlyphId) -> i16 {
+    face.tables()
+        .kern
+        .and_then(|kern| {
+            kern.subtables
+                .into_iter()
+                .filter(|s| s.horizontal && !s.variable && !s.has_cross_stream)
+                .find_map(|s| s.glyphs_kerning(left, right))
+        })
+        .unwrap_or(0)
+}
+
+/// Feeds a glyph outline into a path, flipping from the font's y up units to
+/// y down pixels.
+struct GlyphPen<'a, F: RealNumber> {
+    builder: &'a mut RoughPathBuilder<F>,
+    scale: f64,
+    x: F,
+    y: F,
+}
+
+impl<'a, F: RealNumber> GlyphPen<'a, F> {
+    fn point(&self, x: f32, y: f32) -> (F, F) {
+        (
+            self.x + _cc::<F>(f64::from(x) * self.scale),
+            self.y - _cc::<F>(f64::from(y) * self.scale),
+        )
+    }
+}
+
+impl<'a, F: RealNumber> OutlineBuilder for GlyphPen<'a, F> {
+    fn move_to(&mut self, x: f32, y: f32) {
+        let (x, y) = self.point(x, y);
+        self.builder.move_to(x, y);
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        let (x, y) = self.point(x, y);
+        self.builder.line_to(x, y);
+    }
+
+    fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+        let (x1, y1) = self.point(x1, y1);
+        let (x, y) = self.point(x, y);
+        self.builder.quad_to(x1, y1, x, y);
+    }
+
+    fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+        let (x1, y1) = self.point(x1, y1);
+        let (x2, y2) = self.point(x2, y2);
+        let (x, y) = self.point(x, y);
+        self.builder.cubic_to(x1, y1, x2, y2, x, y);
+    }
+
+    fn close(&mut self) {
+        self.builder.close();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::relative_eq;
+    use svgtypes::PathSegment;
+
+    use super::{text_path, Font, TextOptions, TextOptionsBuilder};
+    use crate::error::Error;
+    use crate::graphics::drawable_maker::{Generator, RoughlyDrawableMakable};
+    use crate::graphics::drawable_ops::OpSet;
+    use crate::graphics::path_builder::RoughPathBuilder;
+
+    /// A font with the glyphs "A", "V" and "o" on a 1000 unit em, a line
+    /// height of 1000 units, advances of 600 units and "AV" kerned by -100.
+    fn test_font() -> Font {
+        Font::from_bytes(include_bytes!("../../tests/data/kern_test.ttf").to_vec()).unwrap()
+    }
+
+    fn move_points(path: &RoughPathBuilder<f64>) -> Vec<(f64, f64)> {
+        path.segments()
+            .iter()
+            .filter_map(|s| match *s {
+                PathSegment::MoveTo { x, y, .. } => Some((x, y)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn invalid_font_data_is_rejected() {
+        assert!(matches!(
+            Font::from_bytes(b"not a font".to_vec()),
+            Err(Error::InvalidFont(_))
+        ));
+        assert!(matches!(
+            Font::from_file("/nonexistent/font.ttf"),
+            Err(Error::InvalidFont(_))
+        ));
+    }
+
+    #[test]
+    fn lines_are_laid_out_on_baselines() {
+        let font = test_font();
+        let options = TextOptionsBuilder::default().size(20.0).build().unwrap();
+        let moves = move_points(&text_path(&font, "o\no", 10.0, 50.0, &options));
+        // an outer and an inner contour per "o", the outer one starting at
+        // (50, 500) in font units
+        assert_eq!(moves.len(), 4);
+        assert!(relative_eq!(moves[0].0, 11.0) && relative_eq!(moves[0].1, 40.0));
+        assert!(relative_eq!(moves[2].0, 11.0) && relative_eq!(moves[2].1, 60.0));
+
+        let generator = Generator::<OpSet<f64>>::default();
+        let drawable = generator.text(&font, "oo", 0.0, 20.0, &TextOptions::default(), &None);
+        assert!(!drawable.opsets.is_empty());
+    }
+
+    #[test]
+    fn kerning_moves_pairs_closer() {
+        let font = test_font();
+        let kerned = TextOptionsBuilder::default().size(20.0).build().unwrap();
+        let unkerned = TextOptionsBuilder::default()
+            .size(20.0)
+            .kerning(false)
+            .build()
+            .unwrap();
+        let v_start =
+            |options: &TextOptions| move_points(&text_path(&font, "AV", 0.0, 0.0, options))[1].0;
+        assert!(relative_eq!(v_start(&unkerned), 12.0));
+        assert!(relative_eq!(v_start(&kerned), 10.0));
+    }
+}
@@ -1,7 +1,10 @@
 pub mod canvas;
+pub mod error;
 pub mod graphics;
 pub mod renderer_engine;
 
+pub use error::Error;
+
 // fn main() {
 //     println!("Hello, world!");
 // }
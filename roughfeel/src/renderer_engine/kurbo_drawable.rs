@@ -3,15 +3,15 @@ use std::ops::MulAssign;
 
 use crate::graphics::_to_f64;
 use crate::graphics::drawable::{DrawOptions, DrawOptionsBuilder, OpSetTrait, RoughlyDrawable};
-use palette::rgb::Rgba;
 use palette::Srgba;
-use piet::kurbo::{BezPath, PathEl, Point};
+use piet::kurbo::{Affine, BezPath, PathEl, Point};
 use piet::{Color, LineJoin, RenderContext, StrokeStyle};
 
 use nalgebra::{Point2, Scalar};
 use nalgebra_glm::RealNumber;
 
 use crate::graphics::drawable_ops::{OpSet, OpSetType, OpType};
+use crate::graphics::paint::FillRule;
 
 use crate::graphics::drawable::Drawable;
 
@@ -65,131 +65,81 @@ impl<FT: RealNumber> Drawable<KurboOpSet<FT>> for KurboDrawable<FT> {
 }
 
 impl<F: RealNumber> KurboDrawable<F> {
+    /// Paints the sets on `ctx`. Outlines and fill sketches are stroked with
+    /// the caps, joins and dashes of the options, and sets whose colour is
+    /// `None` are skipped.
     pub fn draw(&self, ctx: &mut impl RenderContext) {
+        let o = &self.options;
         for set in self.sets.iter() {
             match set.op_set_type {
                 OpSetType::Path => {
-                    ctx.save().expect("Failed to save render context");
-                    if self.options.stroke_line_dash.is_some() {
-                        let stroke_line_dash =
-                            self.options.stroke_line_dash.clone().unwrap_or(Vec::new());
-                        let mut ss = StrokeStyle::new();
-                        ss.set_dash_pattern(stroke_line_dash.as_slice());
-                        ss.set_dash_offset(self.options.stroke_line_dash_offset.unwrap_or(1.0f64));
-                        ss.set_line_cap(convert_line_cap_from_roughr_to_piet(
-                            self.options.line_cap,
-                        ));
-                        ss.set_line_join(convert_line_join_from_roughr_to_piet(
-                            self.options.line_join,
-                        ));
-
-                        let stroke_color = self
-                            .options
-                            .stroke
-                            .unwrap_or_else(|| Srgba::from_components((1.0, 1.0, 1.0, 1.0)));
-                        let rgb: (f32, f32, f32, f32) = stroke_color.into_components();
+                    if let Some(stroke) = o.stroke_color() {
                         ctx.stroke_styled(
                             set.ops.clone(),
-                            &Color::rgba(rgb.0 as f64, rgb.1 as f64, rgb.2 as f64, rgb.3 as f64),
-                            self.options.stroke_width.unwrap_or(1.0) as f64,
-                            &ss,
-                        );
-                        ctx.restore().expect("Failed to restore render context");
-                    } else {
-                        let stroke_color = self
-                            .options
-                            .stroke
-                            .unwrap_or_else(|| Srgba::new(1.0, 1.0, 1.0, 1.0));
-                        let rgb: (f32, f32, f32, f32) = stroke_color.into_components();
-                        ctx.stroke(
-                            set.ops.clone(),
-                            &Color::rgba(rgb.0 as f64, rgb.1 as f64, rgb.2 as f64, rgb.3 as f64),
-                            self.options.stroke_width.unwrap_or(1.0) as f64,
+                            &piet_color(stroke),
+                            o.stroke_width.unwrap_or(1.0) as f64,
+                            &self.stroke_style(&o.stroke_line_dash, o.stroke_line_dash_offset),
                         );
-                        ctx.restore().expect("Failed to restore render context");
                     }
                 }
                 OpSetType::FillPath => {
-                    ctx.save().expect("Failed to save render context");
-                    match self.shape.as_str() {
-                        "curve" | "polygon" | "path" => {
-                            let fill_color =
-                                self.options.fill.unwrap_or(Rgba::new(1.0, 1.0, 1.0, 1.0));
-                            let rgb: (f32, f32, f32, f32) = fill_color.into_components();
-                            ctx.fill_even_odd(
-                                set.ops.clone(),
-                                &Color::rgba(
-                                    rgb.0 as f64,
-                                    rgb.1 as f64,
-                                    rgb.2 as f64,
-                                    rgb.3 as f64,
-                                ),
-                            )
-                        }
-                        _ => {
-                            let fill_color =
-                                self.options.fill.unwrap_or(Rgba::new(1.0, 1.0, 1.0, 1.0));
-                            let rgb: (f32, f32, f32, f32) = fill_color.into_components();
-                            ctx.fill(
-                                set.ops.clone(),
-                                &Color::rgba(
-                                    rgb.0 as f64,
-                                    rgb.1 as f64,
-                                    rgb.2 as f64,
-                                    rgb.3 as f64,
-                                ),
-                            )
+                    if let Some(fill) = o.fill_color() {
+                        match o.fill_rule.unwrap_or_default() {
+                            FillRule::EvenOdd => {
+                                ctx.fill_even_odd(set.ops.clone(), &piet_color(fill))
+                            }
+                            FillRule::NonZero => ctx.fill(set.ops.clone(), &piet_color(fill)),
                         }
                     }
-                    ctx.restore().expect("Failed to restore render context");
                 }
-                OpSetType::FillSketch => {
-                    let mut fweight = self.options.fill_weight.unwrap_or_default();
-                    if fweight < 0.0 {
-                        fweight = self.options.stroke_width.unwrap_or(1.0) / 2.0;
+                OpSetType::BrushPath => {
+                    if let Some(stroke) = o.stroke_color() {
+                        ctx.fill(set.ops.clone(), &piet_color(stroke));
                     }
-                    ctx.save().expect("Failed to save render context");
-
-                    if self.options.fill_line_dash.is_some() {
-                        let fill_line_dash =
-                            self.options.fill_line_dash.clone().unwrap_or_default();
-                        let mut ss = StrokeStyle::new();
-                        ss.set_dash_pattern(fill_line_dash.as_slice());
-                        ss.set_dash_offset(self.options.fill_line_dash_offset.unwrap_or(0.0f64));
-                        ss.set_line_cap(convert_line_cap_from_roughr_to_piet(
-                            self.options.line_cap,
-                        ));
-                        ss.set_line_join(convert_line_join_from_roughr_to_piet(
-                            self.options.line_join,
-                        ));
-                        let fill_color = self
-                            .options
-                            .fill
-                            .unwrap_or_else(|| Rgba::new(1.0, 1.0, 1.0, 1.0));
-                        let rgb: (f32, f32, f32, f32) = fill_color.into_components();
+                }
+                OpSetType::FillSketch => {
+                    if let Some(fill) = o.fill_color() {
+                        let mut fweight = o.fill_weight.unwrap_or_default();
+                        if fweight < 0.0 {
+                            fweight = o.stroke_width.unwrap_or(1.0) / 2.0;
+                        }
                         ctx.stroke_styled(
                             set.ops.clone(),
-                            &Color::rgba(rgb.0 as f64, rgb.1 as f64, rgb.2 as f64, rgb.3 as f64),
-                            fweight as f64,
-                            &ss,
-                        );
-                    } else {
-                        let fill_color = self
-                            .options
-                            .fill
-                            .unwrap_or_else(|| Rgba::new(1.0, 1.0, 1.0, 1.0));
-                        let rgb: (f32, f32, f32, f32) = fill_color.into_components();
-                        ctx.stroke(
-                            set.ops.clone(),
-                            &Color::rgba(rgb.0 as f64, rgb.1 as f64, rgb.2 as f64, rgb.3 as f64),
+                            &piet_color(fill),
                             fweight as f64,
+                            &self.stroke_style(&o.fill_line_dash, o.fill_line_dash_offset),
                         );
                     }
-                    ctx.restore().expect("Failed to restore render context");
                 }
             }
         }
     }
+
+    /// Maps every set through `affine`. Stroke widths are not scaled.
+    pub fn transform(&mut self, affine: &Affine) {
+        self.sets
+            .iter_mut()
+            .for_each(|set| set.ops.apply_affine(*affine));
+    }
+
+    /// The piet stroke style for the options with the given dash pattern.
+    fn stroke_style(&self, dash: &Option<Vec<f64>>, dash_offset: Option<f64>) -> StrokeStyle {
+        let mut style = StrokeStyle::new()
+            .line_cap(convert_line_cap_from_roughr_to_piet(self.options.line_cap))
+            .line_join(convert_line_join_from_roughr_to_piet(
+                self.options.line_join,
+            ));
+        if let Some(dash) = dash.as_ref().filter(|dash| !dash.is_empty()) {
+            style.set_dash_pattern(dash.as_slice());
+            style.set_dash_offset(dash_offset.unwrap_or(0.0));
+        }
+        style
+    }
+}
+
+fn piet_color(color: Srgba) -> Color {
+    let (r, g, b, a) = color.into_components();
+    Color::rgba(r as f64, g as f64, b as f64, a as f64)
 }
 
 pub trait ToKurboOpset<F: RealNumber> {
@@ -272,3 +222,45 @@ fn convert_line_join_from_roughr_to_piet(
         },
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use palette::Srgba;
+    use piet::{LineCap, LineJoin, NullRenderContext};
+
+    use super::ToKurboDrawable;
+    use crate::graphics::drawable::DrawOptionsBuilder;
+    use crate::graphics::drawable_maker::{Generator, RoughlyDrawableMakable};
+    use crate::graphics::drawable_ops::OpSet;
+    use crate::graphics::paint;
+
+    #[test]
+    fn caps_and_joins_apply_without_dashes() {
+        let options = DrawOptionsBuilder::default()
+            .line_cap(paint::LineCap::Round)
+            .line_join(paint::LineJoin::Bevel)
+            .fill_line_dash(vec![3.0, 1.0])
+            .build()
+            .unwrap();
+        let generator = Generator::<OpSet<f64>>::default();
+        let drawable = generator
+            .rectangle(0.0, 0.0, 10.0, 10.0, &Some(options.clone()))
+            .to_kurbo_drawable();
+        let outline = drawable.stroke_style(&options.stroke_line_dash, None);
+        assert_eq!(outline.line_cap, LineCap::Round);
+        assert_eq!(outline.line_join, LineJoin::Bevel);
+        assert!(outline.dash_pattern.is_empty());
+        let fill = drawable.stroke_style(&options.fill_line_dash, None);
+        assert_eq!(&*fill.dash_pattern, &[3.0, 1.0]);
+
+        // unfilled shapes no longer need a white fill or a balanced save
+        let mut ctx = NullRenderContext::new();
+        drawable.draw(&mut ctx);
+        let filled = DrawOptionsBuilder::default()
+            .fill(Srgba::new(0.0, 0.0, 1.0, 1.0))
+            .build()
+            .unwrap();
+        let circle = generator.circle(5.0, 5.0, 8.0, &Some(filled));
+        circle.to_kurbo_drawable().draw(&mut ctx);
+    }
+}
@@ -2,11 +2,15 @@ use std::{fmt::Display, ops::MulAssign};
 
 use nalgebra::{Point2, Scalar};
 use nalgebra_glm::RealNumber;
+use piet::kurbo::PathEl;
 
+use crate::error::Error;
 use crate::graphics::{
+    arrow::ArrowOptions,
     drawable::{DrawOptions, Drawable},
     drawable_maker::{Generator, RoughlyDrawableMakable},
     drawable_ops::OpSet,
+    path_builder::RoughPathBuilder,
 };
 
 use super::kurbo_drawable::{KurboDrawable, KurboOpSet, ToKurboDrawable};
@@ -53,6 +57,21 @@ impl<F: RealNumber + MulAssign + Display, OutputDrawable: Drawable<KurboOpSet<F>
         drawable.to_kurbo_drawable()
     }
 
+    fn rounded_rectangle(
+        &self,
+        x: F,
+        y: F,
+        width: F,
+        height: F,
+        radii: [F; 4],
+        options: &Option<DrawOptions>,
+    ) -> KurboDrawable<F> {
+        let drawable = self
+            .gen
+            .rounded_rectangle(x, y, width, height, radii, options);
+        drawable.to_kurbo_drawable()
+    }
+
     fn ellipse(
         &self,
         x: F,
@@ -85,6 +104,71 @@ impl<F: RealNumber + MulAssign + Display, OutputDrawable: Drawable<KurboOpSet<F>
         drawable.to_kurbo_drawable()
     }
 
+    fn regular_polygon(
+        &self,
+        cx: F,
+        cy: F,
+        r: F,
+        sides: usize,
+        rotation: F,
+        options: &Option<DrawOptions>,
+    ) -> KurboDrawable<F> {
+        let drawable = self
+            .gen
+            .regular_polygon(cx, cy, r, sides, rotation, options);
+        drawable.to_kurbo_drawable()
+    }
+
+    fn star(
+        &self,
+        cx: F,
+        cy: F,
+        outer_r: F,
+        inner_r: F,
+        points: usize,
+        options: &Option<DrawOptions>,
+    ) -> KurboDrawable<F> {
+        let drawable = self.gen.star(cx, cy, outer_r, inner_r, points, options);
+        drawable.to_kurbo_drawable()
+    }
+
+    fn superellipse(
+        &self,
+        cx: F,
+        cy: F,
+        width: F,
+        height: F,
+        n: F,
+        options: &Option<DrawOptions>,
+    ) -> KurboDrawable<F> {
+        let drawable = self.gen.superellipse(cx, cy, width, height, n, options);
+        drawable.to_kurbo_drawable()
+    }
+
+    fn arrow(
+        &self,
+        start: Point2<F>,
+        end: Point2<F>,
+        arrow: &ArrowOptions,
+        options: &Option<DrawOptions>,
+    ) -> KurboDrawable<F> {
+        let drawable = self.gen.arrow(start, end, arrow, options);
+        drawable.to_kurbo_drawable()
+    }
+
+    fn curved_arrow(
+        &self,
+        start: Point2<F>,
+        cp1: Point2<F>,
+        cp2: Point2<F>,
+        end: Point2<F>,
+        arrow: &ArrowOptions,
+        options: &Option<DrawOptions>,
+    ) -> KurboDrawable<F> {
+        let drawable = self.gen.curved_arrow(start, cp1, cp2, end, arrow, options);
+        drawable.to_kurbo_drawable()
+    }
+
     fn arc(
         &self,
         x: F,
@@ -134,4 +218,49 @@ impl<F: RealNumber + MulAssign + Display, OutputDrawable: Drawable<KurboOpSet<F>
         let drawable = self.gen.path(svg_path, options);
         drawable.to_kurbo_drawable()
     }
+
+    fn path_from_builder(
+        &self,
+        path: &RoughPathBuilder<F>,
+        options: &Option<DrawOptions>,
+    ) -> KurboDrawable<F> {
+        let drawable = self.gen.path_from_builder(path, options);
+        drawable.to_kurbo_drawable()
+    }
+
+    fn path_elements(
+        &self,
+        elements: impl IntoIterator<Item = PathEl>,
+        options: &Option<DrawOptions>,
+    ) -> KurboDrawable<F> {
+        let drawable = self.gen.path_elements(elements, options);
+        drawable.to_kurbo_drawable()
+    }
+
+    fn try_path(
+        &self,
+        svg_path: String,
+        options: &Option<DrawOptions>,
+    ) -> Result<KurboDrawable<F>, Error> {
+        let drawable = self.gen.try_path(svg_path, options)?;
+        Ok(drawable.to_kurbo_drawable())
+    }
+
+    fn try_path_elements(
+        &self,
+        elements: impl IntoIterator<Item = PathEl>,
+        options: &Option<DrawOptions>,
+    ) -> Result<KurboDrawable<F>, Error> {
+        let drawable = self.gen.try_path_elements(elements, options)?;
+        Ok(drawable.to_kurbo_drawable())
+    }
+
+    fn try_path_from_builder(
+        &self,
+        path: &RoughPathBuilder<F>,
+        options: &Option<DrawOptions>,
+    ) -> Result<KurboDrawable<F>, Error> {
+        let drawable = self.gen.try_path_from_builder(path, options)?;
+        Ok(drawable.to_kurbo_drawable())
+    }
 }
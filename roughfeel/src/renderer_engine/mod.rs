@@ -1,2 +1,5 @@
 pub mod kurbo_drawable;
 pub mod kurbo_drawable_maker;
+pub mod plotter;
+pub mod rough_render_context;
+pub mod svg_document;
@@ -0,0 +1,383 @@
+use std::fmt::{Display, Write};
+use std::path::Path;
+
+use derive_builder::Builder;
+use nalgebra::Point2;
+use nalgebra_glm::RealNumber;
+use palette::Srgba;
+
+use crate::graphics::_to_f64;
+use crate::graphics::drawable::RoughlyDrawable;
+use crate::graphics::drawable_ops::{OpSet, OpSetType};
+use crate::graphics::paint::FillStyle;
+use crate::graphics::renderer::pattern_fill_polygons;
+
+const MM_PER_INCH: f64 = 25.4;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PlotterUnits {
+    Millimeters,
+    Inches,
+}
+
+#[derive(Clone, Debug, Builder)]
+#[builder(setter(strip_option))]
+pub struct PlotterOptions {
+    /// Maximum deviation, in drawing units, when flattening curves into lines.
+    #[builder(default = "0.5")]
+    pub tolerance: f64,
+    /// Drawing units per inch, which scales drawings onto the plotter bed.
+    #[builder(default = "96.0")]
+    pub dpi: f64,
+    /// Units of the G-code output. HPGL always uses plotter units of 0.025 mm.
+    #[builder(default = "PlotterUnits::Millimeters")]
+    pub units: PlotterUnits,
+    /// Height of the page in drawing units. When set, y is flipped so the
+    /// origin is at the bottom left, as plotters expect.
+    #[builder(default = "None")]
+    pub page_height: Option<f64>,
+    /// Feed rate of pen down moves, in G-code units per minute.
+    #[builder(default = "1500.0")]
+    pub draw_feed_rate: f64,
+    /// Feed rate of pen up moves, in G-code units per minute.
+    #[builder(default = "3000.0")]
+    pub travel_feed_rate: f64,
+    #[builder(default = "\"G0 Z5\".to_owned()")]
+    pub pen_up_command: String,
+    #[builder(default = "\"G0 Z0\".to_owned()")]
+    pub pen_down_command: String,
+    /// Colours of the pens loaded in the plotter; pen `n` is `pens[n - 1]`.
+    /// Strokes use the pen closest to their colour. When empty, every new
+    /// colour is given the next pen number.
+    #[builder(default = "vec![]")]
+    pub pens: Vec<Srgba>,
+}
+
+impl Default for PlotterOptions {
+    fn default() -> Self {
+        PlotterOptionsBuilder::default()
+            .build()
+            .expect("failed to build default plotter options")
+    }
+}
+
+/// A line-only stroke in drawing units, drawn with one pen.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PlotterStroke {
+    pub pen: usize,
+    pub points: Vec<Point2<f64>>,
+}
+
+/// Collects rough drawables as pen strokes and writes them as HPGL or G-code.
+///
+/// Curves are flattened into lines and solid fills are replaced by hachure,
+/// so the output only contains pen up and pen down moves.
+#[derive(Clone, Debug)]
+pub struct PlotterDocument {
+    options: PlotterOptions,
+    pens: Vec<Srgba>,
+    strokes: Vec<PlotterStroke>,
+}
+
+impl PlotterDocument {
+    pub fn new(options: PlotterOptions) -> Self {
+        PlotterDocument {
+            pens: options.pens.clone(),
+            options,
+            strokes: vec![],
+        }
+    }
+
+    pub fn add_drawable<F>(&mut self, drawable: &RoughlyDrawable<OpSet<F>>) -> &mut Self
+    where
+        F: RealNumber + Display,
+    {
+        let o = &drawable.options;
+        let tolerance = F::from_f64(self.options.tolerance).expect("can not parse from f64");
+        for set in drawable.opsets.iter() {
+            let (color, polylines) = match set.op_set_type {
+                // a pen can only trace the outline of a brush stroke
+                OpSetType::Path | OpSetType::BrushPath => (o.stroke, set.to_polylines(tolerance)),
+                OpSetType::FillSketch => (o.fill, set.to_polylines(tolerance)),
+                OpSetType::FillPath => {
+                    let mut hatch_options = o.clone();
+                    if matches!(hatch_options.fill_style, None | Some(FillStyle::Solid)) {
+                        hatch_options.fill_style = Some(FillStyle::Hachure);
+                    }
+                    let hatch: OpSet<F> =
+                        pattern_fill_polygons(set.to_polylines(tolerance), &mut hatch_options);
+                    (o.fill, hatch.to_polylines(tolerance))
+                }
+            };
+            if let Some(color) = color {
+                let pen = self.pen_for(&color);
+                // zero length strokes would only leave dots of ink
+                self.strokes.extend(
+                    polylines
+                        .into_iter()
+                        .filter(|points| points.iter().any(|p| *p != points[0]))
+                        .map(|points| PlotterStroke {
+                            pen,
+                            points: points
+                                .iter()
+                                .map(|p| Point2::new(_to_f64(p.x), _to_f64(p.y)))
+                                .collect(),
+                        }),
+                );
+            }
+        }
+        self
+    }
+
+    pub fn add_drawables<'a, F, I>(&mut self, drawables: I) -> &mut Self
+    where
+        F: RealNumber + Display,
+        I: IntoIterator<Item = &'a RoughlyDrawable<OpSet<F>>>,
+    {
+        drawables.into_iter().for_each(|d| {
+            self.add_drawable(d);
+        });
+        self
+    }
+
+    pub fn strokes(&self) -> &[PlotterStroke] {
+        &self.strokes
+    }
+
+    pub fn strokes_mut(&mut self) -> &mut Vec<PlotterStroke> {
+        &mut self.strokes
+    }
+
+    /// Pen colours, where pen `n` is `pens()[n - 1]`.
+    pub fn pens(&self) -> &[Srgba] {
+        &self.pens
+    }
+
+    pub fn to_hpgl(&self) -> String {
+        let scale = 40.0 * MM_PER_INCH / self.options.dpi;
+        let mut hpgl = String::from("IN;\n");
+        let mut pen = 0;
+        for stroke in self.strokes_by_pen() {
+            if stroke.pen != pen {
+                pen = stroke.pen;
+                writeln!(&mut hpgl, "SP{};", pen).expect("Failed to write hpgl string");
+            }
+            let points: Vec<(i64, i64)> = stroke
+                .points
+                .iter()
+                .map(|p| {
+                    let (x, y) = self.page_point(p);
+                    ((x * scale).round() as i64, (y * scale).round() as i64)
+                })
+                .collect();
+            writeln!(&mut hpgl, "PU{},{};", points[0].0, points[0].1)
+                .expect("Failed to write hpgl string");
+            let draw = points[1..]
+                .iter()
+                .map(|(x, y)| format!("{},{}", x, y))
+                .collect::<Vec<String>>()
+                .join(",");
+            writeln!(&mut hpgl, "PD{};", draw).expect("Failed to write hpgl string");
+        }
+        hpgl.push_str("PU;\nSP0;\n");
+        hpgl
+    }
+
+    pub fn to_gcode(&self) -> String {
+        let o = &self.options;
+        let scale = match o.units {
+            PlotterUnits::Millimeters => MM_PER_INCH / o.dpi,
+            PlotterUnits::Inches => 1.0 / o.dpi,
+        };
+        let mut gcode = String::new();
+        writeln!(
+            &mut gcode,
+            "{}\nG90",
+            match o.units {
+                PlotterUnits::Millimeters => "G21",
+                PlotterUnits::Inches => "G20",
+            }
+        )
+        .expect("Failed to write gcode string");
+        writeln!(&mut gcode, "{}", o.pen_up_command).expect("Failed to write gcode string");
+        let mut pen = 0;
+        for stroke in self.strokes_by_pen() {
+            if stroke.pen != pen {
+                pen = stroke.pen;
+                writeln!(&mut gcode, "T{} M6", pen).expect("Failed to write gcode string");
+            }
+            for (i, p) in stroke.points.iter().enumerate() {
+                let (x, y) = self.page_point(p);
+                let (x, y) = (x * scale, y * scale);
+                if i == 0 {
+                    writeln!(
+                        &mut gcode,
+                        "G0 X{:.3} Y{:.3} F{}\n{}",
+                        x, y, o.travel_feed_rate, o.pen_down_command
+                    )
+                } else {
+                    writeln!(&mut gcode, "G1 X{:.3} Y{:.3} F{}", x, y, o.draw_feed_rate)
+                }
+                .expect("Failed to write gcode string");
+            }
+            writeln!(&mut gcode, "{}", o.pen_up_command).expect("Failed to write gcode string");
+        }
+        gcode.push_str("G0 X0 Y0\nM2\n");
+        gcode
+    }
+
+    pub fn save_hpgl<P: AsRef<Path>>(&self, path: P) -> std::io::Result<()> {
+        std::fs::write(path, self.to_hpgl())
+    }
+
+    pub fn save_gcode<P: AsRef<Path>>(&self, path: P) -> std::io::Result<()> {
+        std::fs::write(path, self.to_gcode())
+    }
+
+    /// Strokes grouped by pen to avoid pen changes, keeping the drawing order
+    /// within each pen.
+    fn strokes_by_pen(&self) -> Vec<&PlotterStroke> {
+        let mut strokes: Vec<&PlotterStroke> =
+            self.strokes.iter().filter(|s| s.points.len() > 1).collect();
+        strokes.sort_by_key(|s| s.pen);
+        strokes
+    }
+
+    fn page_point(&self, p: &Point2<f64>) -> (f64, f64) {
+        match self.options.page_height {
+            Some(height) => (p.x, height - p.y),
+            None => (p.x, p.y),
+        }
+    }
+
+    fn pen_for(&mut self, color: &Srgba) -> usize {
+        if !self.options.pens.is_empty() {
+            let distance = |pen: &Srgba| {
+                (pen.red - color.red).powi(2)
+                    + (pen.green - color.green).powi(2)
+                    + (pen.blue - color.blue).powi(2)
+            };
+            return self
+                .pens
+                .iter()
+                .enumerate()
+                .min_by(|(_, a), (_, b)| distance(a).total_cmp(&distance(b)))
+                .map(|(i, _)| i + 1)
+                .unwrap_or(1);
+        }
+        match self.pens.iter().position(|pen| pen == color) {
+            Some(i) => i + 1,
+            None => {
+                self.pens.push(*color);
+                self.pens.len()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use palette::Srgba;
+
+    use super::{PlotterDocument, PlotterOptions, PlotterOptionsBuilder, PlotterUnits};
+    use crate::graphics::drawable::{DrawOptions, DrawOptionsBuilder, RoughlyDrawable};
+    use crate::graphics::drawable_maker::{Generator, RoughlyDrawableMakable};
+    use crate::graphics::drawable_ops::{Op, OpSet, OpSetType, OpType};
+    use crate::graphics::paint::FillStyle;
+
+    #[test]
+    fn solid_fill_becomes_hatch_and_colours_get_pens() {
+        let options = DrawOptionsBuilder::default()
+            .stroke(Srgba::new(1.0, 0.0, 0.0, 1.0))
+            .fill(Srgba::new(0.0, 0.0, 1.0, 1.0))
+            .fill_style(FillStyle::Solid)
+            .build()
+            .unwrap();
+        let generator = Generator::<OpSet<f64>>::new(options.clone());
+        let circle = generator.circle(50.0, 50.0, 60.0, &Some(options));
+        let mut document = PlotterDocument::new(PlotterOptions::default());
+        document.add_drawable(&circle);
+
+        assert_eq!(document.pens().len(), 2);
+        // the solid fill was hatched into many separate pen strokes
+        assert!(document.strokes().iter().filter(|s| s.pen == 1).count() > 3);
+        assert!(document.strokes().iter().all(|s| s.points.len() >= 2));
+
+        let hpgl = document.to_hpgl();
+        assert!(hpgl.starts_with("IN;\n"));
+        assert!(hpgl.contains("SP1;") && hpgl.contains("SP2;"));
+        assert!(hpgl.ends_with("PU;\nSP0;\n"));
+    }
+
+    /// An L shaped stroke from the origin, 96 px right and 48 px down.
+    fn corner() -> RoughlyDrawable<OpSet<f64>> {
+        let op = |op, x, y| Op {
+            op,
+            data: vec![x, y],
+        };
+        RoughlyDrawable {
+            shape: "path".into(),
+            options: DrawOptions::default(),
+            opsets: vec![OpSet {
+                op_set_type: OpSetType::Path,
+                ops: vec![
+                    op(OpType::Move, 0.0, 0.0),
+                    op(OpType::LineTo, 96.0, 0.0),
+                    op(OpType::LineTo, 96.0, 48.0),
+                ],
+                size: None,
+                path: None,
+            }],
+            fill_region: vec![],
+        }
+    }
+
+    #[test]
+    fn gcode_uses_units_feed_rates_and_pen_commands() {
+        let options = PlotterOptionsBuilder::default()
+            .units(PlotterUnits::Inches)
+            .page_height(48.0)
+            .draw_feed_rate(100.0)
+            .pen_up_command("M5".to_owned())
+            .pen_down_command("M3 S90".to_owned())
+            .build()
+            .unwrap();
+        let mut document = PlotterDocument::new(options);
+        document.add_drawable(&corner());
+
+        // 96 px is one inch at 96 dpi, and y is flipped on the 48 px page
+        assert_eq!(
+            document.to_gcode(),
+            "G20\nG90\nM5\nT1 M6\n\
+             G0 X0.000 Y0.500 F3000\nM3 S90\n\
+             G1 X1.000 Y0.500 F100\n\
+             G1 X1.000 Y0.000 F100\n\
+             M5\nG0 X0 Y0\nM2\n"
+        );
+        // HPGL counts 40 units per millimetre, so an inch is 1016 units
+        assert_eq!(
+            document.to_hpgl(),
+            "IN;\nSP1;\nPU0,508;\nPD1016,508,1016,0;\nPU;\nSP0;\n"
+        );
+    }
+
+    #[test]
+    fn strokes_use_the_closest_loaded_pen() {
+        let options = PlotterOptionsBuilder::default()
+            .pens(vec![
+                Srgba::new(0.0, 0.0, 0.0, 1.0),
+                Srgba::new(0.9, 0.1, 0.1, 1.0),
+            ])
+            .build()
+            .unwrap();
+        let draw_options = DrawOptionsBuilder::default()
+            .stroke(Srgba::new(1.0, 0.0, 0.0, 1.0))
+            .build()
+            .unwrap();
+        let generator = Generator::<OpSet<f64>>::default();
+        let line = generator.line(0.0, 0.0, 10.0, 10.0, &Some(draw_options));
+        let mut document = PlotterDocument::new(options);
+        document.add_drawable(&line);
+        assert!(document.strokes().iter().all(|s| s.pen == 2));
+    }
+}
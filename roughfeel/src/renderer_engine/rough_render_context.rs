@@ -0,0 +1,328 @@
+use std::borrow::Cow;
+
+use palette::Srgba;
+use piet::kurbo::{Affine, Point, Rect, Shape};
+use piet::{
+    Color, Error, FixedGradient, ImageFormat, InterpolationMode, IntoBrush, RenderContext,
+    StrokeStyle,
+};
+
+use crate::graphics::drawable::DrawOptions;
+use crate::graphics::drawable_maker::{Generator, RoughlyDrawableMakable};
+use crate::graphics::drawable_ops::{OpSet, OpSetType};
+use crate::graphics::paint::FillRule;
+use crate::renderer_engine::kurbo_drawable::{KurboDrawable, ToKurboDrawable};
+
+/// A brush of the wrapped render context.
+#[derive(Clone)]
+pub struct RoughBrush<B: Clone>(pub B);
+
+/// A piet [`RenderContext`] proxy that redraws every stroke and fill in a
+/// hand-drawn style.
+///
+/// `stroke`, `stroke_styled`, `fill` and `fill_even_odd` are run through a
+/// [`Generator`] and the resulting sketch is painted on the inner context with
+/// the caller's brush. Lines, rectangles and circles use the matching rough
+/// shapes; every other shape is sketched as a path. Text, images, clipping
+/// and transforms are forwarded untouched.
+pub struct RoughRenderContext<'a, R: RenderContext> {
+    inner: &'a mut R,
+    generator: Generator<OpSet<f64>>,
+}
+
+impl<'a, R: RenderContext> RoughRenderContext<'a, R> {
+    pub fn new(inner: &'a mut R) -> Self {
+        RoughRenderContext {
+            inner,
+            generator: Generator::default(),
+        }
+    }
+
+    /// Creates a proxy that sketches with `options`. Colours always come from
+    /// the brushes passed to the drawing calls.
+    pub fn with_options(inner: &'a mut R, options: DrawOptions) -> Self {
+        RoughRenderContext {
+            inner,
+            generator: Generator::new(options),
+        }
+    }
+
+    pub fn options(&self) -> &DrawOptions {
+        self.generator.default_options()
+    }
+
+    pub fn inner(&mut self) -> &mut R {
+        self.inner
+    }
+
+    fn sketch(&self, shape: &impl Shape, options: DrawOptions) -> KurboDrawable<f64> {
+        let options = Some(options);
+        let drawable = if let Some(line) = shape.as_line() {
+            self.generator
+                .line(line.p0.x, line.p0.y, line.p1.x, line.p1.y, &options)
+        } else if let Some(rect) = shape.as_rect() {
+            self.generator
+                .rectangle(rect.x0, rect.y0, rect.width(), rect.height(), &options)
+        } else if let Some(circle) = shape.as_circle() {
+            self.generator.circle(
+                circle.center.x,
+                circle.center.y,
+                circle.radius * 2.0,
+                &options,
+            )
+        } else {
+            self.generator.shape(shape, &options)
+        };
+        drawable.to_kurbo_drawable()
+    }
+
+    fn stroke_options(&self, width: f64) -> DrawOptions {
+        // The colour only makes the generator emit the outline, the brush paints it
+        let mut options = self.generator.default_options().clone();
+        options.stroke = Some(Srgba::new(0.0, 0.0, 0.0, 1.0));
+        options.stroke_width = Some(width as f32);
+        options.fill = None;
+        options
+    }
+
+    fn fill_options(&self, rule: FillRule) -> DrawOptions {
+        let mut options = self.generator.default_options().clone();
+        options.stroke = None;
+        options.fill = Some(Srgba::new(0.0, 0.0, 0.0, 1.0));
+        options.fill_rule = Some(rule);
+        options
+    }
+
+    fn paint(
+        &mut self,
+        sketch: &KurboDrawable<f64>,
+        brush: &R::Brush,
+        style: Option<&StrokeStyle>,
+    ) {
+        let o = &sketch.options;
+        for set in sketch.sets.iter() {
+            match set.op_set_type {
+                OpSetType::Path => {
+                    let width = o.stroke_width.unwrap_or(1.0) as f64;
+                    match style {
+                        Some(style) => {
+                            self.inner
+                                .stroke_styled(set.ops.clone(), brush, width, style)
+                        }
+                        None => self.inner.stroke(set.ops.clone(), brush, width),
+                    }
+                }
+                OpSetType::FillPath => match o.fill_rule.unwrap_or_default() {
+                    FillRule::EvenOdd => self.inner.fill_even_odd(set.ops.clone(), brush),
+                    FillRule::NonZero => self.inner.fill(set.ops.clone(), brush),
+                },
+                OpSetType::BrushPath => self.inner.fill(set.ops.clone(), brush),
+                OpSetType::FillSketch => {
+                    let mut fill_weight = o.fill_weight.unwrap_or_default();
+                    if fill_weight < 0.0 {
+                        fill_weight = o.stroke_width.unwrap_or(1.0) / 2.0;
+                    }
+                    self.inner
+                        .stroke(set.ops.clone(), brush, fill_weight as f64);
+                }
+            }
+        }
+    }
+
+    fn inner_brush(
+        &mut self,
+        shape: &impl Shape,
+        brush: &impl IntoBrush<Self>,
+    ) -> RoughBrush<R::Brush> {
+        brush.make_brush(self, || shape.bounding_box()).into_owned()
+    }
+}
+
+impl<'a, R: RenderContext> IntoBrush<RoughRenderContext<'a, R>> for RoughBrush<R::Brush> {
+    fn make_brush<'b>(
+        &'b self,
+        _piet: &mut RoughRenderContext<'a, R>,
+        _bbox: impl FnOnce() -> Rect,
+    ) -> Cow<'b, RoughBrush<R::Brush>> {
+        Cow::Borrowed(self)
+    }
+}
+
+impl<'a, R: RenderContext> RenderContext for RoughRenderContext<'a, R> {
+    type Brush = RoughBrush<R::Brush>;
+    type Text = R::Text;
+    type TextLayout = R::TextLayout;
+    type Image = R::Image;
+
+    fn status(&mut self) -> Result<(), Error> {
+        self.inner.status()
+    }
+
+    fn solid_brush(&mut self, color: Color) -> Self::Brush {
+        RoughBrush(self.inner.solid_brush(color))
+    }
+
+    fn gradient(&mut self, gradient: impl Into<FixedGradient>) -> Result<Self::Brush, Error> {
+        Ok(RoughBrush(self.inner.gradient(gradient)?))
+    }
+
+    fn clear(&mut self, region: impl Into<Option<Rect>>, color: Color) {
+        self.inner.clear(region, color);
+    }
+
+    fn stroke(&mut self, shape: impl Shape, brush: &impl IntoBrush<Self>, width: f64) {
+        let brush = self.inner_brush(&shape, brush);
+        let sketch = self.sketch(&shape, self.stroke_options(width));
+        self.paint(&sketch, &brush.0, None);
+    }
+
+    fn stroke_styled(
+        &mut self,
+        shape: impl Shape,
+        brush: &impl IntoBrush<Self>,
+        width: f64,
+        style: &StrokeStyle,
+    ) {
+        let brush = self.inner_brush(&shape, brush);
+        let sketch = self.sketch(&shape, self.stroke_options(width));
+        self.paint(&sketch, &brush.0, Some(style));
+    }
+
+    fn fill(&mut self, shape: impl Shape, brush: &impl IntoBrush<Self>) {
+        let brush = self.inner_brush(&shape, brush);
+        let sketch = self.sketch(&shape, self.fill_options(FillRule::NonZero));
+        self.paint(&sketch, &brush.0, None);
+    }
+
+    fn fill_even_odd(&mut self, shape: impl Shape, brush: &impl IntoBrush<Self>) {
+        let brush = self.inner_brush(&shape, brush);
+        let sketch = self.sketch(&shape, self.fill_options(FillRule::EvenOdd));
+        self.paint(&sketch, &brush.0, None);
+    }
+
+    fn clip(&mut self, shape: impl Shape) {
+        self.inner.clip(shape);
+    }
+
+    fn text(&mut self) -> &mut Self::Text {
+        self.inner.text()
+    }
+
+    fn draw_text(&mut self, layout: &Self::TextLayout, pos: impl Into<Point>) {
+        self.inner.draw_text(layout, pos);
+    }
+
+    fn save(&mut self) -> Result<(), Error> {
+        self.inner.save()
+    }
+
+    fn restore(&mut self) -> Result<(), Error> {
+        self.inner.restore()
+    }
+
+    fn finish(&mut self) -> Result<(), Error> {
+        self.inner.finish()
+    }
+
+    fn transform(&mut self, transform: Affine) {
+        self.inner.transform(transform);
+    }
+
+    fn make_image(
+        &mut self,
+        width: usize,
+        height: usize,
+        buf: &[u8],
+        format: ImageFormat,
+    ) -> Result<Self::Image, Error> {
+        self.inner.make_image(width, height, buf, format)
+    }
+
+    fn draw_image(
+        &mut self,
+        image: &Self::Image,
+        dst_rect: impl Into<Rect>,
+        interp: InterpolationMode,
+    ) {
+        self.inner.draw_image(image, dst_rect, interp);
+    }
+
+    fn draw_image_area(
+        &mut self,
+        image: &Self::Image,
+        src_rect: impl Into<Rect>,
+        dst_rect: impl Into<Rect>,
+        interp: InterpolationMode,
+    ) {
+        self.inner
+            .draw_image_area(image, src_rect, dst_rect, interp);
+    }
+
+    fn capture_image_area(&mut self, src_rect: impl Into<Rect>) -> Result<Self::Image, Error> {
+        self.inner.capture_image_area(src_rect)
+    }
+
+    fn blurred_rect(&mut self, rect: Rect, blur_radius: f64, brush: &impl IntoBrush<Self>) {
+        let brush = self.inner_brush(&rect, brush);
+        self.inner.blurred_rect(rect, blur_radius, &brush.0);
+    }
+
+    fn current_transform(&self) -> Affine {
+        self.inner.current_transform()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use piet::kurbo::{BezPath, Circle, Line, Rect};
+    use piet::{Color, NullRenderContext, RenderContext};
+
+    use super::RoughRenderContext;
+    use crate::graphics::drawable_ops::OpSetType;
+    use crate::graphics::paint::FillRule;
+
+    #[test]
+    fn shapes_map_to_rough_primitives() {
+        let mut inner = NullRenderContext::new();
+        let ctx = RoughRenderContext::new(&mut inner);
+        let options = ctx.stroke_options(2.0);
+        assert_eq!(
+            ctx.sketch(&Line::new((0.0, 0.0), (10.0, 10.0)), options.clone())
+                .shape,
+            "line"
+        );
+        assert_eq!(
+            ctx.sketch(&Rect::new(0.0, 0.0, 10.0, 5.0), options.clone())
+                .shape,
+            "rectangle"
+        );
+        assert_eq!(
+            ctx.sketch(&Circle::new((5.0, 5.0), 5.0), options.clone())
+                .shape,
+            "circle"
+        );
+        let mut path = BezPath::new();
+        path.move_to((0.0, 0.0));
+        path.quad_to((10.0, 20.0), (20.0, 0.0));
+        let sketch = ctx.sketch(&path, options);
+        assert_eq!(sketch.shape, "path");
+        assert!(sketch.sets.iter().all(|s| s.op_set_type == OpSetType::Path));
+    }
+
+    #[test]
+    fn fills_only_produce_fill_sets() {
+        let mut inner = NullRenderContext::new();
+        let mut ctx = RoughRenderContext::new(&mut inner);
+        let sketch = ctx.sketch(
+            &Rect::new(0.0, 0.0, 10.0, 5.0),
+            ctx.fill_options(FillRule::NonZero),
+        );
+        assert!(!sketch.sets.is_empty());
+        assert!(sketch.sets.iter().all(|s| s.op_set_type != OpSetType::Path));
+
+        let brush = ctx.solid_brush(Color::BLACK);
+        ctx.fill(Circle::new((5.0, 5.0), 5.0), &brush);
+        ctx.stroke(Rect::new(0.0, 0.0, 10.0, 5.0), &Color::BLACK, 1.0);
+        ctx.finish().unwrap();
+    }
+}
@@ -0,0 +1,318 @@
+use std::fmt::{Display, Write};
+use std::path::Path;
+
+use nalgebra_glm::RealNumber;
+use palette::Srgba;
+
+use crate::graphics::drawable::{DrawOptions, RoughlyDrawable};
+use crate::graphics::drawable_ops::{OpSet, OpSetType};
+use crate::graphics::paint::{FillRule, LineCap, LineJoin};
+use crate::graphics::path_data::ops_to_path_data;
+
+/// A standalone SVG document that rough drawables can be written into.
+///
+/// Every drawable added to the document becomes one `<g>` element holding a
+/// `<path>` per op set, styled from the drawable's `DrawOptions`.
+#[derive(Clone, Debug)]
+pub struct SvgDocument {
+    width: f64,
+    height: f64,
+    view_box: Option<[f64; 4]>,
+    background: Option<Srgba>,
+    groups: Vec<String>,
+}
+
+impl SvgDocument {
+    pub fn new(width: f64, height: f64) -> Self {
+        SvgDocument {
+            width,
+            height,
+            view_box: None,
+            background: None,
+            groups: vec![],
+        }
+    }
+
+    pub fn set_view_box(&mut self, min_x: f64, min_y: f64, width: f64, height: f64) -> &mut Self {
+        self.view_box = Some([min_x, min_y, width, height]);
+        self
+    }
+
+    /// Sets the colour of a rectangle painted behind all drawables.
+    pub fn set_background(&mut self, background: Option<Srgba>) -> &mut Self {
+        self.background = background;
+        self
+    }
+
+    pub fn add_drawable<F>(&mut self, drawable: &RoughlyDrawable<OpSet<F>>) -> &mut Self
+    where
+        F: RealNumber + Display,
+    {
+        self.groups.push(drawable.to_svg_group());
+        self
+    }
+
+    pub fn add_drawables<'a, F, I>(&mut self, drawables: I) -> &mut Self
+    where
+        F: RealNumber + Display,
+        I: IntoIterator<Item = &'a RoughlyDrawable<OpSet<F>>>,
+    {
+        drawables.into_iter().for_each(|d| {
+            self.add_drawable(d);
+        });
+        self
+    }
+
+    pub fn to_svg_string(&self) -> String {
+        let mut svg = String::new();
+        let [min_x, min_y, vb_width, vb_height] =
+            self.view_box.unwrap_or([0.0, 0.0, self.width, self.height]);
+        writeln!(
+            &mut svg,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"{} {} {} {}\">",
+            self.width, self.height, min_x, min_y, vb_width, vb_height
+        )
+        .expect("Failed to write svg string");
+        if let Some(background) = self.background {
+            writeln!(
+                &mut svg,
+                "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\" fill-opacity=\"{}\"/>",
+                min_x,
+                min_y,
+                vb_width,
+                vb_height,
+                svg_color(&background),
+                background.alpha
+            )
+            .expect("Failed to write svg string");
+        }
+        for group in self.groups.iter() {
+            svg.push_str(group);
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    pub fn save_to_file<P: AsRef<Path>>(&self, path: P) -> std::io::Result<()> {
+        std::fs::write(path, self.to_svg_string())
+    }
+}
+
+impl Display for SvgDocument {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.to_svg_string())
+    }
+}
+
+pub trait ToSvgGroup {
+    fn to_svg_group(&self) -> String;
+}
+
+impl<F: RealNumber + Display> ToSvgGroup for RoughlyDrawable<OpSet<F>> {
+    fn to_svg_group(&self) -> String {
+        let o = &self.options;
+        let fixed_decimals = o.fixed_decimal_place_digits.map(|d| d.max(0.0) as u32);
+        let mut group = String::new();
+        writeln!(
+            &mut group,
+            "<g class=\"{}\">",
+            escape_attribute(&self.shape)
+        )
+        .expect("Failed to write svg string");
+        for set in self.opsets.iter() {
+            let attributes = match set.op_set_type {
+                OpSetType::Path => o.stroke_color().map(|stroke| {
+                    stroke_attributes(
+                        &stroke,
+                        o.stroke_width.unwrap_or(1.0),
+                        &o.stroke_line_dash,
+                        o.stroke_line_dash_offset,
+                        o,
+                    )
+                }),
+                OpSetType::FillPath => o.fill_color().map(|fill| {
+                    let fill_rule = match o.fill_rule.unwrap_or_default() {
+                        FillRule::EvenOdd => "evenodd",
+                        FillRule::NonZero => "nonzero",
+                    };
+                    format!(
+                        "fill=\"{}\" fill-opacity=\"{}\" fill-rule=\"{}\" stroke=\"none\"",
+                        svg_color(&fill),
+                        fill.alpha,
+                        fill_rule
+                    )
+                }),
+                OpSetType::BrushPath => o.stroke_color().map(|stroke| {
+                    format!(
+                        "fill=\"{}\" fill-opacity=\"{}\" stroke=\"none\"",
+                        svg_color(&stroke),
+                        stroke.alpha
+                    )
+                }),
+                OpSetType::FillSketch => o.fill_color().map(|fill| {
+                    let mut fill_weight = o.fill_weight.unwrap_or_default();
+                    if fill_weight < 0.0 {
+                        fill_weight = o.stroke_width.unwrap_or(1.0) / 2.0;
+                    }
+                    stroke_attributes(
+                        &fill,
+                        fill_weight,
+                        &o.fill_line_dash,
+                        o.fill_line_dash_offset,
+                        o,
+                    )
+                }),
+            };
+            if let Some(attributes) = attributes {
+                writeln!(
+                    &mut group,
+                    "<path d=\"{}\" {}/>",
+                    ops_to_path_data(&set.ops, fixed_decimals),
+                    attributes
+                )
+                .expect("Failed to write svg string");
+            }
+        }
+        group.push_str("</g>\n");
+        group
+    }
+}
+
+fn stroke_attributes(
+    color: &Srgba,
+    width: f32,
+    line_dash: &Option<Vec<f64>>,
+    line_dash_offset: Option<f64>,
+    o: &DrawOptions,
+) -> String {
+    let mut attributes = format!(
+        "fill=\"none\" stroke=\"{}\" stroke-opacity=\"{}\" stroke-width=\"{}\"",
+        svg_color(color),
+        color.alpha,
+        width
+    );
+    if let Some(dash) = line_dash {
+        if !dash.is_empty() {
+            let dash_array = dash
+                .iter()
+                .map(|d| d.to_string())
+                .collect::<Vec<String>>()
+                .join(" ");
+            write!(&mut attributes, " stroke-dasharray=\"{}\"", dash_array)
+                .expect("Failed to write svg string");
+            if let Some(offset) = line_dash_offset {
+                write!(&mut attributes, " stroke-dashoffset=\"{}\"", offset)
+                    .expect("Failed to write svg string");
+            }
+        }
+    }
+    if let Some(line_cap) = o.line_cap {
+        let cap = match line_cap {
+            LineCap::Butt => "butt",
+            LineCap::Round => "round",
+            LineCap::Square => "square",
+        };
+        write!(&mut attributes, " stroke-linecap=\"{}\"", cap).expect("Failed to write svg string");
+    }
+    if let Some(line_join) = o.line_join {
+        match line_join {
+            LineJoin::Miter { limit } => write!(
+                &mut attributes,
+                " stroke-linejoin=\"miter\" stroke-miterlimit=\"{}\"",
+                limit
+            ),
+            LineJoin::Round => write!(&mut attributes, " stroke-linejoin=\"round\""),
+            LineJoin::Bevel => write!(&mut attributes, " stroke-linejoin=\"bevel\""),
+        }
+        .expect("Failed to write svg string");
+    }
+    attributes
+}
+
+fn svg_color(color: &Srgba) -> String {
+    let c: Srgba<u8> = color.into_format();
+    format!("#{:02x}{:02x}{:02x}", c.red, c.green, c.blue)
+}
+
+fn escape_attribute(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use palette::Srgba;
+
+    use super::{SvgDocument, ToSvgGroup};
+    use crate::graphics::drawable::DrawOptionsBuilder;
+    use crate::graphics::drawable_maker::{Generator, RoughlyDrawableMakable};
+    use crate::graphics::drawable_ops::OpSet;
+    use crate::graphics::paint::{FillStyle, LineCap, LineJoin};
+
+    #[test]
+    fn rectangle_group_honors_styling() {
+        let options = DrawOptionsBuilder::default()
+            .stroke(Srgba::new(1.0, 0.0, 0.0, 0.5))
+            .fill(Srgba::new(0.0, 0.0, 1.0, 1.0))
+            .fill_style(FillStyle::Hachure)
+            .fill_weight(3.0)
+            .stroke_line_dash(vec![4.0, 2.0])
+            .stroke_line_dash_offset(1.0)
+            .line_cap(LineCap::Round)
+            .line_join(LineJoin::Bevel)
+            .build()
+            .unwrap();
+        let generator = Generator::<OpSet<f64>>::new(options.clone());
+        let rectangle = generator.rectangle(10.0, 10.0, 50.0, 30.0, &Some(options));
+        let group = rectangle.to_svg_group();
+
+        assert!(group.starts_with("<g class=\"rectangle\">"));
+        assert!(group.contains("stroke=\"#ff0000\" stroke-opacity=\"0.5\""));
+        assert!(group.contains("stroke=\"#0000ff\" stroke-opacity=\"1\" stroke-width=\"3\""));
+        assert!(group.contains("stroke-dasharray=\"4 2\" stroke-dashoffset=\"1\""));
+        assert!(group.contains("stroke-linecap=\"round\""));
+        assert!(group.contains("stroke-linejoin=\"bevel\""));
+        assert_eq!(group.matches("<path d=\"M").count(), 2);
+    }
+
+    #[test]
+    fn opacities_multiply_colour_alpha() {
+        let options = DrawOptionsBuilder::default()
+            .stroke(Srgba::new(1.0, 0.0, 0.0, 0.5))
+            .fill(Srgba::new(0.0, 0.0, 1.0, 1.0))
+            .fill_style(FillStyle::Solid)
+            .stroke_opacity(0.5)
+            .fill_opacity(0.75)
+            .opacity(0.5)
+            .build()
+            .unwrap();
+        let generator = Generator::<OpSet<f64>>::default();
+        let group = generator
+            .rectangle(0.0, 0.0, 10.0, 10.0, &Some(options))
+            .to_svg_group();
+        assert!(group.contains("stroke=\"#ff0000\" stroke-opacity=\"0.125\""));
+        assert!(group.contains("fill=\"#0000ff\" fill-opacity=\"0.375\""));
+    }
+
+    #[test]
+    fn document_with_view_box_and_background() {
+        let generator = Generator::<OpSet<f32>>::default();
+        let line = generator.line(0.0, 0.0, 10.0, 10.0, &None);
+        let mut document = SvgDocument::new(200.0, 100.0);
+        document
+            .set_view_box(0.0, 0.0, 20.0, 10.0)
+            .set_background(Some(Srgba::new(1.0, 1.0, 1.0, 1.0)))
+            .add_drawables([&line, &line]);
+        let svg = document.to_svg_string();
+
+        assert!(svg.starts_with(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"200\" height=\"100\" viewBox=\"0 0 20 10\">"
+        ));
+        assert!(svg.contains("<rect x=\"0\" y=\"0\" width=\"20\" height=\"10\" fill=\"#ffffff\""));
+        assert_eq!(svg.matches("<g class=\"line\">").count(), 2);
+        assert!(svg.ends_with("</svg>\n"));
+    }
+}